@@ -15,13 +15,36 @@ struct Order {
     #[serde(rename = "contactPerson")]
     contact_person: String,
     phone: String,
+    #[serde(default)]
+    gstin: String,
     status: String,
+    #[serde(default = "default_doc_type")]
+    #[serde(rename = "docType")]
+    doc_type: String,
     #[serde(rename = "machineName")]
     machine_name: String,
     items: Vec<OrderItem>,
     subtotal: f64,
+    #[serde(default = "default_discount_type")]
+    #[serde(rename = "discountType")]
+    discount_type: String,
+    #[serde(default)]
+    #[serde(rename = "discountValue")]
+    discount_value: f64,
+    #[serde(default)]
+    #[serde(rename = "discountAmount")]
+    discount_amount: f64,
     gst: f64,
+    #[serde(default)]
+    #[serde(rename = "gstRate")]
+    gst_rate: f64,
     total: f64,
+    #[serde(default)]
+    #[serde(rename = "amountPaid")]
+    amount_paid: f64,
+    #[serde(default)]
+    #[serde(rename = "paymentStatus")]
+    payment_status: String,
     remarks: String,
     #[serde(rename = "deliveryNote")]
     delivery_note: String,
@@ -33,6 +56,24 @@ struct Order {
     buyer_order_date: String,
     #[serde(rename = "createdDate")]
     created_date: String,
+    #[serde(default)]
+    #[serde(rename = "updatedAt")]
+    updated_at: String,
+    #[serde(default)]
+    version: u32,
+    #[serde(default)]
+    locked: bool,
+    #[serde(default)]
+    priority: i32,
+    #[serde(default)]
+    #[serde(rename = "changeSeq")]
+    change_seq: i64,
+    #[serde(default)]
+    #[serde(rename = "printedAt")]
+    printed_at: Option<String>,
+    #[serde(default)]
+    #[serde(rename = "dueDate")]
+    due_date: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -41,6 +82,8 @@ struct OrderItem {
     sl_no: u32,
     #[serde(rename = "type")]
     item_type: String,
+    #[serde(default)]
+    hsn: String,
     qty: f64,
     length: String,
     dia: String,
@@ -50,6 +93,382 @@ struct OrderItem {
     amount: f64,
 }
 
+impl OrderItem {
+    // The amount the order form computes: qty * rate, or the rate alone for lump-sum
+    // lines entered with a quantity of 0
+    fn expected_amount(&self) -> f64 {
+        if self.qty == 0.0 {
+            self.rate
+        } else {
+            self.qty * self.rate
+        }
+    }
+}
+
+const DOC_TYPE_ORDER: &str = "order";
+const DOC_TYPE_QUOTATION: &str = "quotation";
+
+fn default_doc_type() -> String {
+    DOC_TYPE_ORDER.to_string()
+}
+
+const DISCOUNT_NONE: &str = "none";
+const DISCOUNT_FLAT: &str = "flat";
+const DISCOUNT_PERCENT: &str = "percent";
+
+fn default_discount_type() -> String {
+    DISCOUNT_NONE.to_string()
+}
+
+impl Order {
+    // Checks the fields the database and documents depend on, collecting every problem
+    // rather than stopping at the first so a caller can report them all at once
+    fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+
+        if self.order_no.trim().is_empty() {
+            errors.push("Order number is required".to_string());
+        }
+        if self.customer_name.trim().is_empty() {
+            errors.push("Customer name is required".to_string());
+        }
+        if !self.gstin.is_empty() {
+            if let Err(e) = validate_gstin(&self.gstin) {
+                errors.push(e);
+            }
+        }
+        if self.date.trim().is_empty() {
+            errors.push("Order date is required".to_string());
+        }
+        if self.status.trim().is_empty() {
+            errors.push("Status is required".to_string());
+        }
+        if self.doc_type != DOC_TYPE_ORDER && self.doc_type != DOC_TYPE_QUOTATION {
+            errors.push(format!("Document type must be \"{}\" or \"{}\"", DOC_TYPE_ORDER, DOC_TYPE_QUOTATION));
+        }
+        if !self.gst_rate.is_finite() || !(0.0..=100.0).contains(&self.gst_rate) {
+            errors.push("GST rate must be between 0 and 100".to_string());
+        }
+        match self.discount_type.as_str() {
+            DISCOUNT_NONE => {}
+            DISCOUNT_FLAT | DISCOUNT_PERCENT => {
+                if !self.discount_value.is_finite() || self.discount_value < 0.0 {
+                    errors.push("Discount must be a non-negative number".to_string());
+                } else if self.discount_type == DISCOUNT_PERCENT && self.discount_value > 100.0 {
+                    errors.push("Discount percentage cannot exceed 100".to_string());
+                } else if self.discount_type == DISCOUNT_FLAT && self.discount_value > self.subtotal {
+                    errors.push("Discount cannot exceed the subtotal".to_string());
+                }
+            }
+            _ => errors.push(format!(
+                "Discount type must be \"{}\", \"{}\" or \"{}\"",
+                DISCOUNT_NONE, DISCOUNT_FLAT, DISCOUNT_PERCENT
+            )),
+        }
+        for (label, value) in [("Subtotal", self.subtotal), ("GST", self.gst), ("Total", self.total)] {
+            if !value.is_finite() || value < 0.0 {
+                errors.push(format!("{} must be a non-negative number", label));
+            }
+        }
+        for item in &self.items {
+            if !item.qty.is_finite() || item.qty < 0.0 {
+                errors.push(format!("Item {}: quantity must be a non-negative number", item.sl_no));
+            }
+            if !item.rate.is_finite() || item.rate < 0.0 {
+                errors.push(format!("Item {}: rate must be a non-negative number", item.sl_no));
+            }
+            if !item.amount.is_finite() || item.amount < 0.0 {
+                errors.push(format!("Item {}: amount must be a non-negative number", item.sl_no));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+// GSTIN layout: 2-digit state code, 10-character PAN (5 letters, 4 digits, 1 letter),
+// entity number, the letter Z, and a mod-36 check character over the first 14
+fn validate_gstin(gstin: &str) -> Result<(), String> {
+    let chars: Vec<char> = gstin.chars().collect();
+    if chars.len() != 15 {
+        return Err(format!("GSTIN {} must be exactly 15 characters", gstin));
+    }
+
+    let well_formed = chars[0..2].iter().all(|c| c.is_ascii_digit())
+        && chars[2..7].iter().all(|c| c.is_ascii_uppercase())
+        && chars[7..11].iter().all(|c| c.is_ascii_digit())
+        && chars[11].is_ascii_uppercase()
+        && (chars[12].is_ascii_digit() || chars[12].is_ascii_uppercase())
+        && chars[12] != '0'
+        && chars[13] == 'Z'
+        && (chars[14].is_ascii_digit() || chars[14].is_ascii_uppercase());
+    if !well_formed {
+        return Err(format!("GSTIN {} is not in the format 22AAAAA0000A1Z5", gstin));
+    }
+
+    const CODE_POINTS: &str = "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+    let mut sum = 0;
+    for (i, c) in chars[0..14].iter().enumerate() {
+        let value = CODE_POINTS.find(*c).unwrap_or(0);
+        let product = value * if i % 2 == 0 { 1 } else { 2 };
+        sum += product / 36 + product % 36;
+    }
+    let check = CODE_POINTS.as_bytes()[(36 - sum % 36) % 36] as char;
+    if chars[14] != check {
+        return Err(format!("GSTIN {} has an invalid check character", gstin));
+    }
+
+    Ok(())
+}
+
+// The phone as shown on screen and in print: trimmed, with runs of whitespace collapsed
+fn display_phone(phone: &str) -> String {
+    phone.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// Canonical digits-only form used for searching. The field often holds more than one
+// number ("9440624313, 9550884200"), so each is normalized separately and the results
+// comma-joined. A +91/91 country code or a leading trunk 0 on an otherwise 10-digit
+// number is dropped, so "+91 94406-24313" and "094406 24313" both become "9440624313".
+fn normalize_phone(phone: &str) -> String {
+    phone
+        .split([',', '/', ';'])
+        .map(|part| {
+            let digits: String = part.chars().filter(|c| c.is_ascii_digit()).collect();
+            match digits.len() {
+                12 if digits.starts_with("91") => digits[2..].to_string(),
+                11 if digits.starts_with('0') => digits[1..].to_string(),
+                _ => digits,
+            }
+        })
+        .filter(|digits| !digits.is_empty())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+// Canonical form of an order's `date`: YYYY-MM-DD, which the month grouping and date
+// range filters rely on. Also accepts the DD-MM-YYYY / DD/MM/YYYY forms people type and
+// full timestamps (taken as the local calendar date).
+fn normalize_order_date(value: &str) -> Result<String, AppError> {
+    use chrono::NaiveDate;
+
+    let value = value.trim();
+    let date = ["%Y-%m-%d", "%d-%m-%Y", "%d/%m/%Y"]
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(value, format).ok())
+        .or_else(|| parse_timestamp(value).map(|ts| ts.with_timezone(&chrono::Local).date_naive()))
+        .ok_or_else(|| AppError::Validation(format!("Order date '{}' is not a valid date (expected YYYY-MM-DD)", value)))?;
+    Ok(date.format("%Y-%m-%d").to_string())
+}
+
+// Canonical form of `created_date`: a UTC timestamp in the same format as toISOString()
+// and updated_at, so all three sort and compare as strings
+fn normalize_created_date(value: &str) -> Result<String, AppError> {
+    let value = value.trim();
+    let timestamp = parse_timestamp(value)
+        .ok_or_else(|| AppError::Validation(format!("Created date '{}' is not a valid ISO-8601 date/time", value)))?;
+    Ok(timestamp.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string())
+}
+
+// Bounds of an inclusive from..=to range of order dates, as report and export commands
+// take them. Both must be YYYY-MM-DD, since they're compared with `date` as strings and
+// anything else would silently leave orders out.
+fn parse_date_range(from: &str, to: &str) -> Result<(chrono::NaiveDate, chrono::NaiveDate), AppError> {
+    let parse = |value: &str| {
+        chrono::NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d")
+            .map_err(|_| AppError::Validation(format!("Invalid date '{}' (expected YYYY-MM-DD)", value)))
+    };
+    let (start, end) = (parse(from)?, parse(to)?);
+    if start > end {
+        return Err(AppError::Validation(format!("From date {} is after to date {}", from, to)));
+    }
+    Ok((start, end))
+}
+
+// RFC 3339 timestamps keep their offset; ones without an offset (or bare dates, taken as
+// midnight) are in the shop PC's local time
+fn parse_timestamp(value: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Some(timestamp.with_timezone(&Utc));
+    }
+    let naive = ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .or_else(|| NaiveDate::parse_from_str(value, "%Y-%m-%d").ok().and_then(|d| d.and_hms_opt(0, 0, 0)))?;
+    naive
+        .and_local_timezone(chrono::Local)
+        .earliest()
+        .map(|local| local.with_timezone(&Utc))
+}
+
+// Header-only view of an order for list screens that never show line items
+#[derive(Debug, Serialize, Clone)]
+struct OrderSummary {
+    #[serde(rename = "orderNo")]
+    order_no: String,
+    date: String,
+    #[serde(rename = "customerName")]
+    customer_name: String,
+    #[serde(rename = "contactPerson")]
+    contact_person: String,
+    phone: String,
+    gstin: String,
+    status: String,
+    #[serde(rename = "docType")]
+    doc_type: String,
+    #[serde(rename = "machineName")]
+    machine_name: String,
+    subtotal: f64,
+    #[serde(rename = "discountType")]
+    discount_type: String,
+    #[serde(rename = "discountValue")]
+    discount_value: f64,
+    #[serde(rename = "discountAmount")]
+    discount_amount: f64,
+    gst: f64,
+    #[serde(rename = "gstRate")]
+    gst_rate: f64,
+    total: f64,
+    #[serde(rename = "amountPaid")]
+    amount_paid: f64,
+    #[serde(rename = "paymentStatus")]
+    payment_status: String,
+    remarks: String,
+    #[serde(rename = "deliveryNote")]
+    delivery_note: String,
+    #[serde(rename = "deliveryNoteDate")]
+    delivery_note_date: String,
+    #[serde(rename = "buyerOrderNo")]
+    buyer_order_no: String,
+    #[serde(rename = "buyerOrderDate")]
+    buyer_order_date: String,
+    #[serde(rename = "createdDate")]
+    created_date: String,
+    #[serde(rename = "updatedAt")]
+    updated_at: String,
+    version: u32,
+    locked: bool,
+    priority: i32,
+    #[serde(rename = "changeSeq")]
+    change_seq: i64,
+    #[serde(rename = "printedAt")]
+    printed_at: Option<String>,
+    #[serde(rename = "dueDate")]
+    due_date: Option<String>,
+}
+
+impl From<Order> for OrderSummary {
+    fn from(order: Order) -> Self {
+        OrderSummary {
+            order_no: order.order_no,
+            date: order.date,
+            customer_name: order.customer_name,
+            contact_person: order.contact_person,
+            phone: order.phone,
+            gstin: order.gstin,
+            status: order.status,
+            doc_type: order.doc_type,
+            machine_name: order.machine_name,
+            subtotal: order.subtotal,
+            discount_type: order.discount_type,
+            discount_value: order.discount_value,
+            discount_amount: order.discount_amount,
+            gst: order.gst,
+            gst_rate: order.gst_rate,
+            total: order.total,
+            amount_paid: order.amount_paid,
+            payment_status: order.payment_status,
+            remarks: order.remarks,
+            delivery_note: order.delivery_note,
+            delivery_note_date: order.delivery_note_date,
+            buyer_order_no: order.buyer_order_no,
+            buyer_order_date: order.buyer_order_date,
+            created_date: order.created_date,
+            updated_at: order.updated_at,
+            version: order.version,
+            locked: order.locked,
+            priority: order.priority,
+            change_seq: order.change_seq,
+            printed_at: order.printed_at,
+            due_date: order.due_date,
+        }
+    }
+}
+
+// Error returned to the frontend as { kind, message } so the UI can react per error type
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", content = "message")]
+enum AppError {
+    NotFound(String),
+    Conflict(String),
+    Locked(String),
+    Validation(String),
+    ReadOnly(String),
+    // The user stopped a long-running command
+    Cancelled(String),
+    Internal(String),
+}
+
+impl AppError {
+    // Classifies a rusqlite error and prefixes its message with context
+    fn db(context: &str, e: rusqlite::Error) -> AppError {
+        use rusqlite::ErrorCode;
+
+        let message = format!("{}: {}", context, e);
+        match &e {
+            rusqlite::Error::QueryReturnedNoRows => AppError::NotFound(message),
+            rusqlite::Error::SqliteFailure(err, _) => match err.code {
+                ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked => AppError::Locked(message),
+                ErrorCode::ConstraintViolation => AppError::Conflict(message),
+                _ => AppError::Internal(message),
+            },
+            _ => AppError::Internal(message),
+        }
+    }
+}
+
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::NotFound(msg)
+            | AppError::Conflict(msg)
+            | AppError::Locked(msg)
+            | AppError::Validation(msg)
+            | AppError::ReadOnly(msg)
+            | AppError::Cancelled(msg)
+            | AppError::Internal(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+// Viewer kiosks set AAKSO_READONLY (to anything but "", "0" or "false") so that every
+// command that would change the database refuses up front. Read once at first use.
+fn read_only_mode() -> bool {
+    static READ_ONLY: std::sync::OnceLock<bool> = std::sync::OnceLock::new();
+    *READ_ONLY.get_or_init(|| match std::env::var("AAKSO_READONLY") {
+        Ok(value) => {
+            let value = value.trim();
+            !(value.is_empty() || value == "0" || value.eq_ignore_ascii_case("false"))
+        }
+        Err(_) => false,
+    })
+}
+
+fn ensure_writable() -> Result<(), AppError> {
+    if read_only_mode() {
+        return Err(AppError::ReadOnly(
+            "This terminal is in read-only mode; orders can be viewed but not changed".to_string(),
+        ));
+    }
+    Ok(())
+}
+
 fn get_db_path() -> PathBuf {
     // Use executable's directory for database file (portable)
     // This allows shipping orders.db alongside the exe
@@ -66,11 +485,144 @@ fn get_db_path() -> PathBuf {
     path
 }
 
-fn init_database() -> SqlResult<Connection> {
-    let db_path = get_db_path();
-    let conn = Connection::open(&db_path)?;
+// A single schema change, applied once and recorded in the schema_version table
+struct Migration {
+    version: u32,
+    description: &'static str,
+    up: fn(&Connection) -> SqlResult<()>,
+}
 
-    // Create orders table
+// Ordered list of schema migrations. Never edit or reorder an entry that has shipped;
+// append a new one with the next version number instead.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "create orders and order_items tables",
+        up: migrate_create_tables,
+    },
+    Migration {
+        version: 2,
+        description: "add machine, delivery note and buyer order columns to legacy orders tables",
+        up: migrate_add_order_detail_columns,
+    },
+    Migration {
+        version: 3,
+        description: "drop legacy machine column from order_items",
+        up: migrate_drop_item_machine_column,
+    },
+    Migration {
+        version: 4,
+        description: "add status and date indexes",
+        up: migrate_add_indexes,
+    },
+    Migration {
+        version: 5,
+        description: "create order_attachments table",
+        up: migrate_create_attachments,
+    },
+    Migration {
+        version: 6,
+        description: "add gstin column to orders",
+        up: migrate_add_gstin,
+    },
+    Migration {
+        version: 7,
+        description: "add hsn column to order_items",
+        up: migrate_add_item_hsn,
+    },
+    Migration {
+        version: 8,
+        description: "add doc_type column to orders",
+        up: migrate_add_doc_type,
+    },
+    Migration {
+        version: 9,
+        description: "create settings table",
+        up: migrate_create_settings,
+    },
+    Migration {
+        version: 10,
+        description: "create company_profile table",
+        up: migrate_create_company_profile,
+    },
+    Migration {
+        version: 11,
+        description: "add orders.updated_at",
+        up: migrate_add_updated_at,
+    },
+    Migration {
+        version: 12,
+        description: "add orders.amount_paid and payment_status",
+        up: migrate_add_payment_columns,
+    },
+    Migration {
+        version: 13,
+        description: "create payments table",
+        up: migrate_create_payments,
+    },
+    Migration {
+        version: 14,
+        description: "add orders.phone_normalized",
+        up: migrate_add_phone_normalized,
+    },
+    Migration {
+        version: 15,
+        description: "add orders.version",
+        up: migrate_add_order_version,
+    },
+    Migration {
+        version: 16,
+        description: "add orders.gst_rate",
+        up: migrate_add_gst_rate,
+    },
+    Migration {
+        version: 17,
+        description: "add orders discount columns",
+        up: migrate_add_discount,
+    },
+    Migration {
+        version: 18,
+        description: "add orders.locked",
+        up: migrate_add_locked,
+    },
+    Migration {
+        version: 19,
+        description: "create audit_log table",
+        up: migrate_create_audit_log,
+    },
+    Migration {
+        version: 20,
+        description: "add buyer_order_no index",
+        up: migrate_add_buyer_order_index,
+    },
+    Migration {
+        version: 21,
+        description: "add orders.priority",
+        up: migrate_add_priority,
+    },
+    Migration {
+        version: 22,
+        description: "add orders.invoice_hash",
+        up: migrate_add_invoice_hash,
+    },
+    Migration {
+        version: 23,
+        description: "add orders.change_seq and triggers maintaining it",
+        up: migrate_add_change_seq,
+    },
+    Migration {
+        version: 24,
+        description: "add orders.printed_at",
+        up: migrate_add_printed_at,
+    },
+    Migration {
+        version: 25,
+        description: "add orders.due_date",
+        up: migrate_add_due_date,
+    },
+];
+
+fn migrate_create_tables(conn: &Connection) -> SqlResult<()> {
     conn.execute(
         "CREATE TABLE IF NOT EXISTS orders (
             order_no TEXT PRIMARY KEY,
@@ -93,267 +645,971 @@ fn init_database() -> SqlResult<Connection> {
         [],
     )?;
 
-    // Add new columns to existing tables (migration)
-    let _ = conn.execute("ALTER TABLE orders ADD COLUMN machine_name TEXT", []);
-    let _ = conn.execute("ALTER TABLE orders ADD COLUMN delivery_note TEXT", []);
-    let _ = conn.execute("ALTER TABLE orders ADD COLUMN delivery_note_date TEXT", []);
-    let _ = conn.execute("ALTER TABLE orders ADD COLUMN buyer_order_no TEXT", []);
-    let _ = conn.execute("ALTER TABLE orders ADD COLUMN buyer_order_date TEXT", []);
-
-    // Check if order_items table exists and has the old 'machine' column
-    let table_exists: bool = conn.query_row(
-        "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='order_items'",
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS order_items (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            order_no TEXT NOT NULL,
+            sl_no INTEGER NOT NULL,
+            item_type TEXT,
+            qty REAL NOT NULL,
+            length TEXT,
+            dia TEXT,
+            shore TEXT,
+            remarks TEXT,
+            rate REAL NOT NULL,
+            amount REAL NOT NULL,
+            FOREIGN KEY (order_no) REFERENCES orders(order_no) ON DELETE CASCADE
+        )",
         [],
-        |row| Ok(row.get::<_, i32>(0)? > 0),
-    ).unwrap_or(false);
-    
-    if table_exists {
-        // Check if machine column exists
-        let mut has_machine = false;
-        let mut check_stmt = conn.prepare("PRAGMA table_info(order_items)")?;
-        let columns = check_stmt.query_map([], |row| {
-            let name: String = row.get(1)?;
-            Ok(name)
-        })?;
-        
-        for column in columns {
-            if let Ok(col_name) = column {
-                if col_name == "machine" {
-                    has_machine = true;
-                    break;
-                }
-            }
-        }
-        
-        // If machine column exists, we need to migrate
-        if has_machine {
-            // Create new table without machine column
-            conn.execute(
-                "CREATE TABLE IF NOT EXISTS order_items_new (
-                    id INTEGER PRIMARY KEY AUTOINCREMENT,
-                    order_no TEXT NOT NULL,
-                    sl_no INTEGER NOT NULL,
-                    item_type TEXT,
-                    qty REAL NOT NULL,
-                    length TEXT,
-                    dia TEXT,
-                    shore TEXT,
-                    remarks TEXT,
-                    rate REAL NOT NULL,
-                    amount REAL NOT NULL,
-                    FOREIGN KEY (order_no) REFERENCES orders(order_no) ON DELETE CASCADE
-                )",
-                [],
-            )?;
-            
-            // Copy data (excluding machine column)
-            conn.execute(
-                "INSERT INTO order_items_new (id, order_no, sl_no, item_type, qty, length, dia, shore, remarks, rate, amount)
-                 SELECT id, order_no, sl_no, item_type, qty, length, dia, shore, remarks, rate, amount FROM order_items",
-                [],
-            )?;
-            
-            // Drop old table
-            conn.execute("DROP TABLE order_items", [])?;
-            
-            // Rename new table
-            conn.execute("ALTER TABLE order_items_new RENAME TO order_items", [])?;
-        }
-    } else {
-        // Create order_items table if it doesn't exist
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS order_items (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                order_no TEXT NOT NULL,
-                sl_no INTEGER NOT NULL,
-                item_type TEXT,
-                qty REAL NOT NULL,
-                length TEXT,
-                dia TEXT,
-                shore TEXT,
-                remarks TEXT,
-                rate REAL NOT NULL,
-                amount REAL NOT NULL,
-                FOREIGN KEY (order_no) REFERENCES orders(order_no) ON DELETE CASCADE
-            )",
-            [],
-        )?;
+    )?;
+
+    Ok(())
+}
+
+fn migrate_add_order_detail_columns(conn: &Connection) -> SqlResult<()> {
+    // Databases created before these columns existed still have the original orders table
+    add_column_if_missing(conn, "orders", "machine_name", "TEXT")?;
+    add_column_if_missing(conn, "orders", "delivery_note", "TEXT")?;
+    add_column_if_missing(conn, "orders", "delivery_note_date", "TEXT")?;
+    add_column_if_missing(conn, "orders", "buyer_order_no", "TEXT")?;
+    add_column_if_missing(conn, "orders", "buyer_order_date", "TEXT")?;
+    Ok(())
+}
+
+fn migrate_drop_item_machine_column(conn: &Connection) -> SqlResult<()> {
+    if !column_exists(conn, "order_items", "machine")? {
+        return Ok(());
     }
 
-    // Create index for faster queries
+    // Create new table without machine column
     conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_order_status ON orders(status)",
+        "CREATE TABLE order_items_new (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            order_no TEXT NOT NULL,
+            sl_no INTEGER NOT NULL,
+            item_type TEXT,
+            qty REAL NOT NULL,
+            length TEXT,
+            dia TEXT,
+            shore TEXT,
+            remarks TEXT,
+            rate REAL NOT NULL,
+            amount REAL NOT NULL,
+            FOREIGN KEY (order_no) REFERENCES orders(order_no) ON DELETE CASCADE
+        )",
         [],
     )?;
 
+    // Copy data (excluding machine column)
     conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_order_date ON orders(date)",
+        "INSERT INTO order_items_new (id, order_no, sl_no, item_type, qty, length, dia, shore, remarks, rate, amount)
+         SELECT id, order_no, sl_no, item_type, qty, length, dia, shore, remarks, rate, amount FROM order_items",
         [],
     )?;
 
-    Ok(conn)
-}
-
-fn get_connection() -> SqlResult<Connection> {
-    init_database()
-}
+    conn.execute("DROP TABLE order_items", [])?;
+    conn.execute("ALTER TABLE order_items_new RENAME TO order_items", [])?;
 
-fn load_orders_from_db() -> Result<Vec<Order>, String> {
-    load_orders_paginated_from_db(None, None)
+    Ok(())
 }
 
-fn load_orders_paginated_from_db(page: Option<u32>, page_size: Option<u32>) -> Result<Vec<Order>, String> {
-    let conn = get_connection().map_err(|e| format!("Database error: {}", e))?;
-    
-    let query = if let (Some(p), Some(ps)) = (page, page_size) {
-        let offset = (p - 1) * ps;
-        format!("SELECT order_no, date, customer_name, contact_person, phone, status, machine_name, subtotal, gst, total, remarks, delivery_note, delivery_note_date, buyer_order_no, buyer_order_date, created_date FROM orders ORDER BY created_date DESC LIMIT {} OFFSET {}", ps, offset)
-    } else {
-        // Load all orders if pagination not specified
-        "SELECT order_no, date, customer_name, contact_person, phone, status, machine_name, subtotal, gst, total, remarks, delivery_note, delivery_note_date, buyer_order_no, buyer_order_date, created_date FROM orders ORDER BY created_date DESC".to_string()
-    };
+fn migrate_add_indexes(conn: &Connection) -> SqlResult<()> {
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_order_status ON orders(status)",
+        [],
+    )?;
 
-    let mut stmt = conn
-        .prepare(&query)
-        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_order_date ON orders(date)",
+        [],
+    )?;
 
-    let order_iter = stmt
-        .query_map([], |row| {
-            Ok(Order {
-                order_no: row.get(0)?,
-                date: row.get(1)?,
-                customer_name: row.get(2)?,
-                contact_person: row.get(3).unwrap_or_default(),
-                phone: row.get(4).unwrap_or_default(),
-                status: row.get(5)?,
-                machine_name: row.get(6).unwrap_or_default(),
-                items: Vec::new(), // Will be loaded separately
-                subtotal: row.get(7)?,
-                gst: row.get(8)?,
-                total: row.get(9)?,
-                remarks: row.get(10).unwrap_or_default(),
-                delivery_note: row.get(11).unwrap_or_default(),
-                delivery_note_date: row.get(12).unwrap_or_default(),
-                buyer_order_no: row.get(13).unwrap_or_default(),
-                buyer_order_date: row.get(14).unwrap_or_default(),
-                created_date: row.get(15)?,
-            })
-        })
-        .map_err(|e| format!("Failed to query orders: {}", e))?;
+    Ok(())
+}
 
-    let mut orders = Vec::new();
-    for order_result in order_iter {
-        let mut order = order_result.map_err(|e| format!("Failed to parse order: {}", e))?;
-        
-        // Load items for this order
-        let mut item_stmt = conn
-            .prepare("SELECT sl_no, item_type, qty, length, dia, shore, remarks, rate, amount FROM order_items WHERE order_no = ? ORDER BY sl_no")
-            .map_err(|e| format!("Failed to prepare items query: {}", e))?;
-
-        let item_iter = item_stmt
-            .query_map([&order.order_no], |row| {
-                Ok(OrderItem {
-                    sl_no: row.get(0)?,
-                    item_type: row.get(1).unwrap_or_default(),
-                    qty: row.get(2)?,
-                    length: row.get(3).unwrap_or_default(),
-                    dia: row.get(4).unwrap_or_default(),
-                    shore: row.get(5).unwrap_or_default(),
-                    remarks: row.get(6).unwrap_or_default(),
-                    rate: row.get(7)?,
-                    amount: row.get(8)?,
-                })
-            })
-            .map_err(|e| format!("Failed to query items: {}", e))?;
+fn migrate_create_attachments(conn: &Connection) -> SqlResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS order_attachments (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            order_no TEXT NOT NULL,
+            file_path TEXT NOT NULL,
+            label TEXT,
+            added_at TEXT NOT NULL,
+            FOREIGN KEY (order_no) REFERENCES orders(order_no) ON DELETE CASCADE
+        )",
+        [],
+    )?;
 
-        let mut items = Vec::new();
-        for item_result in item_iter {
-            items.push(item_result.map_err(|e| format!("Failed to parse item: {}", e))?);
-        }
-        order.items = items;
-        orders.push(order);
-    }
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_attachment_order ON order_attachments(order_no)",
+        [],
+    )?;
 
-    Ok(orders)
+    Ok(())
 }
 
-fn save_order_to_db(order: &Order) -> Result<(), String> {
-    let mut conn = get_connection().map_err(|e| format!("Database error: {}", e))?;
+fn migrate_add_gstin(conn: &Connection) -> SqlResult<()> {
+    add_column_if_missing(conn, "orders", "gstin", "TEXT")
+}
 
-    // Start transaction
-    let tx = conn.transaction().map_err(|e| format!("Failed to start transaction: {}", e))?;
+fn migrate_add_item_hsn(conn: &Connection) -> SqlResult<()> {
+    add_column_if_missing(conn, "order_items", "hsn", "TEXT DEFAULT ''")
+}
 
-    // Insert order
-    tx.execute(
-        "INSERT OR REPLACE INTO orders (order_no, date, customer_name, contact_person, phone, status, machine_name, subtotal, gst, total, remarks, delivery_note, delivery_note_date, buyer_order_no, buyer_order_date, created_date) 
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
-        rusqlite::params![
-            order.order_no,
-            order.date,
-            order.customer_name,
-            order.contact_person,
-            order.phone,
-            order.status,
-            order.machine_name,
-            order.subtotal,
-            order.gst,
-            order.total,
-            order.remarks,
-            order.delivery_note,
-            order.delivery_note_date,
-            order.buyer_order_no,
-            order.buyer_order_date,
-            order.created_date
-        ],
-    )
-    .map_err(|e| format!("Failed to insert order: {}", e))?;
+fn migrate_add_doc_type(conn: &Connection) -> SqlResult<()> {
+    // Everything saved before quotations were tracked separately is treated as an order
+    add_column_if_missing(conn, "orders", "doc_type", "TEXT NOT NULL DEFAULT 'order'")
+}
 
-    // Delete existing items for this order
-    tx.execute(
-        "DELETE FROM order_items WHERE order_no = ?1",
-        [&order.order_no],
-    )
-    .map_err(|e| format!("Failed to delete existing items: {}", e))?;
+fn migrate_create_settings(conn: &Connection) -> SqlResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS settings (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        )",
+        [],
+    )?;
+    Ok(())
+}
 
-    // Insert items
-    for item in &order.items {
-        tx.execute(
-            "INSERT INTO order_items (order_no, sl_no, item_type, qty, length, dia, shore, remarks, rate, amount) 
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
-            rusqlite::params![
-                order.order_no,
-                item.sl_no,
-                item.item_type,
-                item.qty,
-                item.length,
-                item.dia,
-                item.shore,
-                item.remarks,
-                item.rate,
-                item.amount
-            ],
-        )
-        .map_err(|e| format!("Failed to insert item: {}", e))?;
-    }
+fn migrate_create_company_profile(conn: &Connection) -> SqlResult<()> {
+    // Single-row table: the CHECK keeps it to the one profile with id 1
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS company_profile (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            name TEXT NOT NULL,
+            tagline TEXT,
+            address TEXT,
+            gstin TEXT,
+            phone TEXT,
+            email TEXT,
+            logo_path TEXT
+        )",
+        [],
+    )?;
+    Ok(())
+}
 
-    // Commit transaction
-    tx.commit().map_err(|e| format!("Failed to commit transaction: {}", e))?;
+fn migrate_add_updated_at(conn: &Connection) -> SqlResult<()> {
+    add_column_if_missing(conn, "orders", "updated_at", "TEXT DEFAULT ''")?;
+    // Existing rows have never been touched since they were created
+    conn.execute("UPDATE orders SET updated_at = created_date WHERE updated_at IS NULL OR updated_at = ''", [])?;
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_orders_updated_at ON orders(updated_at)", [])?;
+    Ok(())
+}
 
+fn migrate_add_payment_columns(conn: &Connection) -> SqlResult<()> {
+    add_column_if_missing(conn, "orders", "amount_paid", "REAL NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "orders", "payment_status", "TEXT NOT NULL DEFAULT 'unpaid'")?;
     Ok(())
 }
 
-fn get_total_orders_count() -> Result<u32, String> {
-    let conn = get_connection().map_err(|e| format!("Database error: {}", e))?;
-    let count: u32 = conn.query_row(
-        "SELECT COUNT(*) FROM orders",
+fn migrate_create_payments(conn: &Connection) -> SqlResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS payments (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            order_no TEXT NOT NULL,
+            amount REAL NOT NULL,
+            paid_on TEXT NOT NULL,
+            method TEXT,
+            FOREIGN KEY (order_no) REFERENCES orders(order_no) ON DELETE CASCADE
+        )",
         [],
-        |row| Ok(row.get(0)?),
-    ).map_err(|e| format!("Failed to get count: {}", e))?;
-    Ok(count)
-}
+    )?;
 
-#[derive(Serialize)]
-struct PaginatedOrders {
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_payment_order ON payments(order_no)",
+        [],
+    )?;
+
+    // amount_paid is now the sum of an order's payments; carry any amount recorded
+    // before payments existed over as a single opening payment so it isn't lost
+    conn.execute(
+        "INSERT INTO payments (order_no, amount, paid_on, method)
+         SELECT order_no, amount_paid, date, 'opening balance' FROM orders WHERE amount_paid > 0",
+        [],
+    )?;
+
+    Ok(())
+}
+
+fn migrate_add_phone_normalized(conn: &Connection) -> SqlResult<()> {
+    add_column_if_missing(conn, "orders", "phone_normalized", "TEXT DEFAULT ''")?;
+
+    // The canonical form is computed in Rust, so backfill row by row
+    let phones = {
+        let mut stmt = conn.prepare("SELECT order_no, phone FROM orders")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?.unwrap_or_default()))
+        })?;
+        rows.collect::<SqlResult<Vec<_>>>()?
+    };
+    for (order_no, phone) in phones {
+        conn.execute(
+            "UPDATE orders SET phone = ?1, phone_normalized = ?2 WHERE order_no = ?3",
+            [display_phone(&phone), normalize_phone(&phone), order_no],
+        )?;
+    }
+
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_orders_phone_normalized ON orders(phone_normalized)", [])?;
+    Ok(())
+}
+
+fn migrate_add_order_version(conn: &Connection) -> SqlResult<()> {
+    add_column_if_missing(conn, "orders", "version", "INTEGER NOT NULL DEFAULT 1")
+}
+
+fn migrate_add_gst_rate(conn: &Connection) -> SqlResult<()> {
+    add_column_if_missing(conn, "orders", "gst_rate", "REAL NOT NULL DEFAULT 0")?;
+    // Older orders only stored the GST amount; recover the percentage it was charged at
+    conn.execute(
+        "UPDATE orders SET gst_rate = ROUND(gst * 100.0 / subtotal, 2) WHERE gst_rate = 0 AND subtotal > 0",
+        [],
+    )?;
+    Ok(())
+}
+
+fn migrate_add_discount(conn: &Connection) -> SqlResult<()> {
+    add_column_if_missing(conn, "orders", "discount_type", "TEXT NOT NULL DEFAULT 'none'")?;
+    add_column_if_missing(conn, "orders", "discount_value", "REAL NOT NULL DEFAULT 0")?;
+    // Derived from the other two on save; stored so reports can sum taxable values in SQL
+    add_column_if_missing(conn, "orders", "discount_amount", "REAL NOT NULL DEFAULT 0")
+}
+
+fn migrate_add_locked(conn: &Connection) -> SqlResult<()> {
+    add_column_if_missing(conn, "orders", "locked", "INTEGER NOT NULL DEFAULT 0")
+}
+
+// No foreign key to orders: entries for deleted orders must outlive them
+fn migrate_create_audit_log(conn: &Connection) -> SqlResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS audit_log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            order_no TEXT NOT NULL,
+            action TEXT NOT NULL,
+            changed_at TEXT NOT NULL,
+            details TEXT NOT NULL DEFAULT ''
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_audit_order ON audit_log(order_no)",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_audit_changed_at ON audit_log(changed_at)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+fn migrate_add_buyer_order_index(conn: &Connection) -> SqlResult<()> {
+    // NOCASE so the case-insensitive exact match in find_by_buyer_order can use it
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_order_buyer_order_no ON orders(buyer_order_no COLLATE NOCASE)",
+        [],
+    )?;
+    Ok(())
+}
+
+fn migrate_add_priority(conn: &Connection) -> SqlResult<()> {
+    add_column_if_missing(conn, "orders", "priority", "INTEGER NOT NULL DEFAULT 0")
+}
+
+// Existing orders are left unhashed until they're next saved; verify_order_hash reports
+// them as having no hash rather than as tampered
+fn migrate_add_invoice_hash(conn: &Connection) -> SqlResult<()> {
+    add_column_if_missing(conn, "orders", "invoice_hash", "TEXT NOT NULL DEFAULT ''")
+}
+
+// change_seq is a database-wide, strictly increasing write counter for the sync feed.
+// Triggers maintain it rather than each UPDATE statement, so no write path (present or
+// future) can forget it. The update trigger only fires when change_seq itself wasn't
+// touched, and SQLite doesn't recurse into triggers by default, so it can't loop.
+fn migrate_add_change_seq(conn: &Connection) -> SqlResult<()> {
+    add_column_if_missing(conn, "orders", "change_seq", "INTEGER NOT NULL DEFAULT 0")?;
+
+    // Existing rows are sequenced in the order they were last written
+    conn.execute(
+        "UPDATE orders SET change_seq = (
+             SELECT COUNT(*) FROM orders o2
+             WHERE o2.updated_at < orders.updated_at
+                OR (o2.updated_at = orders.updated_at AND o2.order_no <= orders.order_no)
+         )",
+        [],
+    )?;
+
+    conn.execute_batch(
+        "CREATE INDEX IF NOT EXISTS idx_orders_change_seq ON orders(change_seq);
+         CREATE TRIGGER IF NOT EXISTS orders_change_seq_insert AFTER INSERT ON orders
+         BEGIN
+             UPDATE orders SET change_seq = (SELECT COALESCE(MAX(change_seq), 0) + 1 FROM orders)
+             WHERE order_no = NEW.order_no;
+         END;
+         CREATE TRIGGER IF NOT EXISTS orders_change_seq_update AFTER UPDATE ON orders
+         WHEN NEW.change_seq = OLD.change_seq
+         BEGIN
+             UPDATE orders SET change_seq = (SELECT COALESCE(MAX(change_seq), 0) + 1 FROM orders)
+             WHERE order_no = NEW.order_no;
+         END;",
+    )
+}
+
+// When the invoice was last printed, or NULL if it hasn't been since the order was last
+// edited; every write path that changes what the invoice shows clears it
+fn migrate_add_printed_at(conn: &Connection) -> SqlResult<()> {
+    add_column_if_missing(conn, "orders", "printed_at", "TEXT")
+}
+
+// The delivery date promised to the customer (YYYY-MM-DD), NULL when none was given
+fn migrate_add_due_date(conn: &Connection) -> SqlResult<()> {
+    add_column_if_missing(conn, "orders", "due_date", "TEXT")?;
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_orders_due_date ON orders(due_date)", [])?;
+    Ok(())
+}
+
+fn column_exists(conn: &Connection, table: &str, column: &str) -> SqlResult<bool> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let columns = stmt.query_map([], |row| row.get::<_, String>(1))?;
+
+    for col_name in columns {
+        if col_name? == column {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+fn add_column_if_missing(conn: &Connection, table: &str, column: &str, definition: &str) -> SqlResult<()> {
+    if !column_exists(conn, table, column)? {
+        conn.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition), [])?;
+    }
+    Ok(())
+}
+
+// Applies every migration newer than the recorded schema version, each in its own transaction
+fn run_migrations(conn: &mut Connection) -> SqlResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS schema_version (
+            version INTEGER PRIMARY KEY,
+            description TEXT NOT NULL,
+            applied_at TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    let current = current_schema_version(conn)?;
+
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+        let tx = conn.transaction()?;
+        (migration.up)(&tx)?;
+        tx.execute(
+            "INSERT INTO schema_version (version, description, applied_at) VALUES (?1, ?2, datetime('now'))",
+            rusqlite::params![migration.version, migration.description],
+        )?;
+        tx.commit()?;
+    }
+
+    Ok(())
+}
+
+fn current_schema_version(conn: &Connection) -> SqlResult<u32> {
+    conn.query_row(
+        "SELECT COALESCE(MAX(version), 0) FROM schema_version",
+        [],
+        |row| row.get(0),
+    )
+}
+
+// Per-connection settings. WAL lets the reporting commands read while a save is writing,
+// foreign keys are off by default in SQLite (ON DELETE CASCADE needs them), and the busy
+// timeout makes a writer wait briefly for another instead of failing straight away.
+fn configure_connection(conn: &Connection) -> SqlResult<()> {
+    conn.execute_batch(
+        "PRAGMA journal_mode = WAL;
+         PRAGMA foreign_keys = ON;
+         PRAGMA busy_timeout = 5000;",
+    )
+}
+
+fn open_database(db_path: &std::path::Path) -> SqlResult<Connection> {
+    let mut conn = Connection::open(db_path)?;
+    configure_connection(&conn)?;
+    run_migrations(&mut conn)?;
+    Ok(conn)
+}
+
+type DbPool = r2d2::Pool<r2d2_sqlite::SqliteConnectionManager>;
+type PooledConnection = r2d2::PooledConnection<r2d2_sqlite::SqliteConnectionManager>;
+
+const DB_POOL_SIZE: u32 = 4;
+
+#[derive(Debug)]
+struct ConnectionCustomizer;
+
+impl r2d2::CustomizeConnection<Connection, rusqlite::Error> for ConnectionCustomizer {
+    fn on_acquire(&self, conn: &mut Connection) -> SqlResult<()> {
+        configure_connection(conn)
+    }
+}
+
+// Migrations run once here on a plain connection before any pooled ones are handed out.
+// The pool is built unchecked so the window still opens if the database can't be
+// reached yet; commands then report the failure when they try to check out a connection.
+fn init_pool(db_path: &std::path::Path) -> DbPool {
+    if let Err(e) = open_database(db_path) {
+        eprintln!("Warning: Failed to initialize database: {}", e);
+    }
+
+    let manager = r2d2_sqlite::SqliteConnectionManager::file(db_path);
+    r2d2::Pool::builder()
+        .max_size(DB_POOL_SIZE)
+        .connection_customizer(Box::new(ConnectionCustomizer))
+        .build_unchecked(manager)
+}
+
+// Every connection in the pool busy (or the database unopenable) for the pool's timeout
+fn checkout(pool: &DbPool) -> Result<PooledConnection, AppError> {
+    pool.get()
+        .map_err(|e| AppError::Locked(format!("Could not get a database connection: {}", e)))
+}
+
+fn load_orders_from_db(conn: &Connection) -> Result<Vec<Order>, AppError> {
+    load_orders_paginated_from_db(conn, None, None, None, false)
+}
+
+const ORDER_COLUMNS: &str = "order_no, date, customer_name, contact_person, phone, status, machine_name, subtotal, gst, total, remarks, delivery_note, delivery_note_date, buyer_order_no, buyer_order_date, created_date, gstin, doc_type, updated_at, amount_paid, payment_status, version, gst_rate, discount_type, discount_value, discount_amount, locked, priority, change_seq, printed_at, due_date";
+
+// Maximum number of rows returned by recent_orders
+const MAX_RECENT_ORDERS: u32 = 100;
+
+// Maps a row selected with ORDER_COLUMNS to an Order (items are loaded separately)
+fn order_from_row(row: &rusqlite::Row) -> SqlResult<Order> {
+    Ok(Order {
+        order_no: row.get(0)?,
+        date: row.get(1)?,
+        customer_name: row.get(2)?,
+        contact_person: row.get(3).unwrap_or_default(),
+        phone: row.get(4).unwrap_or_default(),
+        gstin: row.get(16).unwrap_or_default(),
+        status: row.get(5)?,
+        doc_type: row.get(17)?,
+        machine_name: row.get(6).unwrap_or_default(),
+        items: Vec::new(), // Will be loaded separately
+        subtotal: row.get(7)?,
+        discount_type: row.get(23).unwrap_or_default(),
+        discount_value: row.get(24).unwrap_or_default(),
+        discount_amount: row.get(25).unwrap_or_default(),
+        gst: row.get(8)?,
+        gst_rate: row.get(22).unwrap_or_default(),
+        total: row.get(9)?,
+        amount_paid: row.get(19).unwrap_or_default(),
+        payment_status: row.get(20).unwrap_or_default(),
+        remarks: row.get(10).unwrap_or_default(),
+        delivery_note: row.get(11).unwrap_or_default(),
+        delivery_note_date: row.get(12).unwrap_or_default(),
+        buyer_order_no: row.get(13).unwrap_or_default(),
+        buyer_order_date: row.get(14).unwrap_or_default(),
+        created_date: row.get(15)?,
+        updated_at: row.get(18).unwrap_or_default(),
+        version: row.get(21).unwrap_or_default(),
+        locked: row.get(26).unwrap_or_default(),
+        priority: row.get(27).unwrap_or_default(),
+        change_seq: row.get(28).unwrap_or_default(),
+        printed_at: row.get(29).unwrap_or_default(),
+        due_date: row.get(30).unwrap_or_default(),
+    })
+}
+
+const ITEM_COLUMNS: &str = "sl_no, item_type, qty, length, dia, shore, remarks, rate, amount, hsn";
+
+// ITEM_COLUMNS qualified with a table alias, for queries that join order_items
+fn prefixed_item_columns(alias: &str) -> String {
+    ITEM_COLUMNS
+        .split(", ")
+        .map(|c| format!("{}.{}", alias, c))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// Maps a row selected with ITEM_COLUMNS (in that order, starting at column 0)
+fn item_from_row(row: &rusqlite::Row) -> SqlResult<OrderItem> {
+    Ok(OrderItem {
+        sl_no: row.get(0)?,
+        item_type: row.get(1).unwrap_or_default(),
+        hsn: row.get(9).unwrap_or_default(),
+        qty: row.get(2)?,
+        length: row.get(3).unwrap_or_default(),
+        dia: row.get(4).unwrap_or_default(),
+        shore: row.get(5).unwrap_or_default(),
+        remarks: row.get(6).unwrap_or_default(),
+        rate: row.get(7)?,
+        amount: row.get(8)?,
+    })
+}
+
+fn load_items_for_order(conn: &Connection, order_no: &str) -> Result<Vec<OrderItem>, AppError> {
+    let mut item_stmt = conn
+        .prepare(&format!("SELECT {} FROM order_items WHERE order_no = ? ORDER BY sl_no", ITEM_COLUMNS))
+        .map_err(|e| AppError::db("Failed to prepare items query", e))?;
+
+    let item_iter = item_stmt
+        .query_map([order_no], item_from_row)
+        .map_err(|e| AppError::db("Failed to query items", e))?;
+
+    let mut items = Vec::new();
+    for item_result in item_iter {
+        items.push(item_result.map_err(|e| AppError::db("Failed to parse item", e))?);
+    }
+    Ok(items)
+}
+
+// Newest first, or with by_priority, rush orders (highest priority) first and newest
+// first within a priority
+fn load_orders_paginated_from_db(conn: &Connection, page: Option<u32>, page_size: Option<u32>, doc_type: Option<&str>, by_priority: bool) -> Result<Vec<Order>, AppError> {
+    let order_by = if by_priority { "priority DESC, created_date DESC" } else { "created_date DESC" };
+    let query = if let (Some(p), Some(ps)) = (page, page_size) {
+        let offset = page_offset(p, ps);
+        format!("SELECT {} FROM orders WHERE (?1 IS NULL OR doc_type = ?1) ORDER BY {} LIMIT {} OFFSET {}", ORDER_COLUMNS, order_by, ps, offset)
+    } else {
+        // Load all orders if pagination not specified
+        format!("SELECT {} FROM orders WHERE (?1 IS NULL OR doc_type = ?1) ORDER BY {}", ORDER_COLUMNS, order_by)
+    };
+
+    let mut stmt = conn
+        .prepare(&query)
+        .map_err(|e| AppError::db("Failed to prepare query", e))?;
+
+    let order_iter = stmt
+        .query_map([doc_type], order_from_row)
+        .map_err(|e| AppError::db("Failed to query orders", e))?;
+
+    let mut orders = Vec::new();
+    for order_result in order_iter {
+        let mut order = order_result.map_err(|e| AppError::db("Failed to parse order", e))?;
+        
+        // Load items for this order
+        order.items = load_items_for_order(conn, &order.order_no)?;
+        orders.push(order);
+    }
+
+    Ok(orders)
+}
+
+// Loads a single order with its items, or None if the order number doesn't exist
+fn load_order_by_no(conn: &Connection, order_no: &str) -> Result<Option<Order>, AppError> {
+    use rusqlite::OptionalExtension;
+
+    let order = conn
+        .query_row(
+            &format!("SELECT {} FROM orders WHERE order_no = ?1", ORDER_COLUMNS),
+            [order_no],
+            order_from_row,
+        )
+        .optional()
+        .map_err(|e| AppError::db("Failed to query order", e))?;
+
+    match order {
+        Some(mut order) => {
+            order.items = load_items_for_order(conn, &order.order_no)?;
+            Ok(Some(order))
+        }
+        None => Ok(None),
+    }
+}
+
+// Runs a SELECT of ORDER_COLUMNS and maps every row, optionally loading each order's items
+fn query_orders<P: rusqlite::Params>(conn: &Connection, sql: &str, params: P, with_items: bool) -> Result<Vec<Order>, AppError> {
+    let mut stmt = conn
+        .prepare(sql)
+        .map_err(|e| AppError::db("Failed to prepare query", e))?;
+
+    let order_iter = stmt
+        .query_map(params, order_from_row)
+        .map_err(|e| AppError::db("Failed to query orders", e))?;
+
+    let mut orders = Vec::new();
+    for order_result in order_iter {
+        let mut order = order_result.map_err(|e| AppError::db("Failed to parse order", e))?;
+        if with_items {
+            order.items = load_items_for_order(conn, &order.order_no)?;
+        }
+        orders.push(order);
+    }
+
+    Ok(orders)
+}
+
+fn load_order_summaries_from_db(conn: &Connection, page: u32, page_size: u32) -> Result<Vec<OrderSummary>, AppError> {
+    let offset = page_offset(page, page_size);
+
+    let mut stmt = conn
+        .prepare(&format!("SELECT {} FROM orders ORDER BY created_date DESC LIMIT ?1 OFFSET ?2", ORDER_COLUMNS))
+        .map_err(|e| AppError::db("Failed to prepare query", e))?;
+
+    // No per-order item queries here, which is what makes this cheaper than load_orders
+    let summary_iter = stmt
+        .query_map(rusqlite::params![page_size, offset], order_from_row)
+        .map_err(|e| AppError::db("Failed to query orders", e))?;
+
+    let mut summaries = Vec::new();
+    for order_result in summary_iter {
+        let order = order_result.map_err(|e| AppError::db("Failed to parse order", e))?;
+        summaries.push(OrderSummary::from(order));
+    }
+
+    Ok(summaries)
+}
+
+// Optimistic concurrency: `order.version` is the version the caller last loaded (0 for a
+// new order). If the stored row has moved past it, someone else saved in between and
+// this save is refused rather than silently overwriting their changes. Returns the
+// version now stored.
+fn save_order_to_db(conn: &mut Connection, order: &Order) -> Result<u32, AppError> {
+    let tx = conn.transaction().map_err(|e| AppError::db("Failed to start transaction", e))?;
+    let version = save_order_in_tx(&tx, order)?;
+    tx.commit().map_err(|e| AppError::db("Failed to commit transaction", e))?;
+    Ok(version)
+}
+
+// save_order_to_db's work inside a transaction the caller owns, for callers that write
+// more alongside the order and need it all to land together
+fn save_order_in_tx(tx: &rusqlite::Transaction, order: &Order) -> Result<u32, AppError> {
+    use rusqlite::OptionalExtension;
+
+    let date = normalize_order_date(&order.date)?;
+    let created_date = normalize_created_date(&order.created_date)?;
+    let due_date = order
+        .due_date
+        .as_deref()
+        .map(str::trim)
+        .filter(|d| !d.is_empty())
+        .map(|d| {
+            normalize_order_date(d)
+                .map_err(|_| AppError::Validation(format!("Due date '{}' is not a valid date (expected YYYY-MM-DD)", d)))
+        })
+        .transpose()?;
+
+    let stored: Option<(u32, bool, String)> = tx
+        .query_row("SELECT version, locked, status FROM orders WHERE order_no = ?1", [&order.order_no], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+        })
+        .optional()
+        .map_err(|e| AppError::db("Failed to check order version", e))?;
+    if let Some((_, true, _)) = stored {
+        return Err(AppError::Locked(format!("Order {} is locked; unlock it before making changes", order.order_no)));
+    }
+    let stored_version = stored.as_ref().map(|(version, _, _)| *version);
+    if let Some(stored) = stored_version {
+        if stored > order.version {
+            return Err(AppError::Conflict(format!(
+                "Order {} was changed by someone else (version {}, yours is {}); reload it before saving",
+                order.order_no, stored, order.version
+            )));
+        }
+    }
+
+    // Upsert rather than INSERT OR REPLACE: a REPLACE deletes the existing row first,
+    // which would cascade-delete the order's attachments. updated_at is always stamped
+    // here in the same UTC format as the frontend's toISOString() created_date, so the
+    // two compare as plain strings.
+    tx.execute(
+        "INSERT INTO orders (order_no, date, customer_name, contact_person, phone, status, machine_name, subtotal, gst, total, remarks, delivery_note, delivery_note_date, buyer_order_no, buyer_order_date, created_date, gstin, doc_type, updated_at, phone_normalized, version, gst_rate, discount_type, discount_value, discount_amount, due_date) 
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'), ?19, 1, ?20, ?21, ?22, ?23, ?24)
+         ON CONFLICT(order_no) DO UPDATE SET
+            date = excluded.date,
+            customer_name = excluded.customer_name,
+            contact_person = excluded.contact_person,
+            phone = excluded.phone,
+            status = excluded.status,
+            machine_name = excluded.machine_name,
+            subtotal = excluded.subtotal,
+            gst = excluded.gst,
+            total = excluded.total,
+            remarks = excluded.remarks,
+            delivery_note = excluded.delivery_note,
+            delivery_note_date = excluded.delivery_note_date,
+            buyer_order_no = excluded.buyer_order_no,
+            buyer_order_date = excluded.buyer_order_date,
+            created_date = excluded.created_date,
+            gstin = excluded.gstin,
+            doc_type = excluded.doc_type,
+            updated_at = excluded.updated_at,
+            phone_normalized = excluded.phone_normalized,
+            version = orders.version + 1,
+            gst_rate = excluded.gst_rate,
+            discount_type = excluded.discount_type,
+            discount_value = excluded.discount_value,
+            discount_amount = excluded.discount_amount,
+            due_date = excluded.due_date,
+            printed_at = NULL",
+        rusqlite::params![
+            order.order_no,
+            date,
+            order.customer_name,
+            order.contact_person,
+            display_phone(&order.phone),
+            order.status,
+            order.machine_name,
+            order.subtotal,
+            order.gst,
+            order.total,
+            order.remarks,
+            order.delivery_note,
+            order.delivery_note_date,
+            order.buyer_order_no,
+            order.buyer_order_date,
+            created_date,
+            order.gstin,
+            order.doc_type,
+            normalize_phone(&order.phone),
+            order.gst_rate,
+            order.discount_type,
+            order.discount_value,
+            order.discount_amount,
+            due_date
+        ],
+    )
+    .map_err(|e| AppError::db("Failed to insert order", e))?;
+
+    // Delete existing items for this order
+    tx.execute(
+        "DELETE FROM order_items WHERE order_no = ?1",
+        [&order.order_no],
+    )
+    .map_err(|e| AppError::db("Failed to delete existing items", e))?;
+
+    // Insert items
+    for item in &order.items {
+        tx.execute(
+            "INSERT INTO order_items (order_no, sl_no, item_type, qty, length, dia, shore, remarks, rate, amount, hsn) 
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            rusqlite::params![
+                order.order_no,
+                item.sl_no,
+                item.item_type,
+                item.qty,
+                item.length,
+                item.dia,
+                item.shore,
+                item.remarks,
+                item.rate,
+                item.amount,
+                item.hsn
+            ],
+        )
+        .map_err(|e| AppError::db("Failed to insert item", e))?;
+    }
+
+    // amount_paid isn't part of the form, so the upsert leaves it alone, but a changed
+    // total can move the order between partial and paid
+    refresh_payment_status(tx, &order.order_no)?;
+    stamp_invoice_hash(tx, &order.order_no)?;
+
+    match &stored {
+        Some((_, _, old_status)) => {
+            record_audit(tx, &order.order_no, AUDIT_UPDATED, "")?;
+            record_status_change(tx, &order.order_no, old_status, &order.status)?;
+        }
+        None => record_audit(tx, &order.order_no, AUDIT_CREATED, &order.status)?,
+    }
+
+    Ok(stored_version.map_or(1, |stored| stored + 1))
+}
+
+fn read_setting(conn: &Connection, key: &str) -> Result<Option<String>, AppError> {
+    use rusqlite::OptionalExtension;
+
+    conn.query_row("SELECT value FROM settings WHERE key = ?1", [key], |row| row.get(0))
+        .optional()
+        .map_err(|e| AppError::db("Failed to read setting", e))
+}
+
+fn write_setting(conn: &Connection, key: &str, value: &str) -> Result<(), AppError> {
+    conn.execute(
+        "INSERT INTO settings (key, value) VALUES (?1, ?2)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+        [key, value],
+    )
+    .map_err(|e| AppError::db("Failed to write setting", e))?;
+    Ok(())
+}
+
+const SETTING_ROUNDING_MODE: &str = "rounding_mode";
+const SETTING_GST_RATE: &str = "gst_rate";
+const SETTING_ORDER_PREFIX: &str = "order_prefix";
+// Regex new order numbers must match in full, e.g. ARW-\d{4}; unset means any number
+const SETTING_ORDER_NO_PATTERN: &str = "order_no_pattern";
+
+const SETTING_TALLY_SALES_LEDGER: &str = "tally_sales_ledger";
+const SETTING_TALLY_GST_LEDGER: &str = "tally_gst_ledger";
+
+const DEFAULT_GST_RATE: f64 = 18.0;
+const DEFAULT_ORDER_PREFIX: &str = "ORD";
+
+// Rejects values for known keys that the typed getters wouldn't be able to read back
+fn validate_setting(key: &str, value: &str) -> Result<(), AppError> {
+    match key {
+        SETTING_ROUNDING_MODE => RoundingMode::parse(value).map(|_| ()),
+        SETTING_GST_RATE => match value.parse::<f64>() {
+            Ok(rate) if (0.0..=100.0).contains(&rate) => Ok(()),
+            _ => Err(AppError::Validation(format!("GST rate must be a number between 0 and 100, got {}", value))),
+        },
+        SETTING_ORDER_PREFIX if value.trim().is_empty() => {
+            Err(AppError::Validation("Order number prefix cannot be empty".to_string()))
+        }
+        SETTING_ORDER_NO_PATTERN => order_no_regex(value).map(|_| ()),
+        _ => Ok(()),
+    }
+}
+
+fn get_gst_rate_setting(conn: &Connection) -> Result<f64, AppError> {
+    match read_setting(conn, SETTING_GST_RATE)? {
+        Some(value) => value
+            .parse()
+            .map_err(|_| AppError::Validation(format!("Stored GST rate is not a number: {}", value))),
+        None => Ok(DEFAULT_GST_RATE),
+    }
+}
+
+fn get_order_prefix_setting(conn: &Connection) -> Result<String, AppError> {
+    Ok(read_setting(conn, SETTING_ORDER_PREFIX)?.unwrap_or_else(|| DEFAULT_ORDER_PREFIX.to_string()))
+}
+
+// Anchored so the pattern has to match the whole order number, not just part of it
+fn order_no_regex(pattern: &str) -> Result<regex::Regex, AppError> {
+    regex::Regex::new(&format!("^(?:{})$", pattern.trim()))
+        .map_err(|e| AppError::Validation(format!("Invalid order number pattern: {}", e)))
+}
+
+// The configured order number pattern and its compiled regex, if one is set
+fn order_no_pattern(conn: &Connection) -> Result<Option<(String, regex::Regex)>, AppError> {
+    match read_setting(conn, SETTING_ORDER_NO_PATTERN)?.filter(|p| !p.trim().is_empty()) {
+        Some(pattern) => Ok(Some((pattern.trim().to_string(), order_no_regex(&pattern)?))),
+        None => Ok(None),
+    }
+}
+
+// Checks a new order number against the configured pattern, if there is one. Only new
+// numbers are checked, so orders numbered before the pattern was set stay editable.
+fn ensure_order_no_format(conn: &Connection, order_no: &str) -> Result<(), AppError> {
+    if let Some((pattern, regex)) = order_no_pattern(conn)? {
+        if !regex.is_match(order_no) {
+            return Err(AppError::Validation(format!(
+                "Order number {} doesn't match the required format ({})",
+                order_no, pattern
+            )));
+        }
+    }
+    Ok(())
+}
+
+// How GST and totals are rounded to paise. `None` keeps the raw computed values,
+// which is what the app has always stored.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+enum RoundingMode {
+    #[default]
+    None,
+    HalfUp,
+    HalfEven,
+}
+
+impl RoundingMode {
+    fn parse(value: &str) -> Result<RoundingMode, AppError> {
+        match value {
+            "none" => Ok(RoundingMode::None),
+            "half_up" => Ok(RoundingMode::HalfUp),
+            "half_even" => Ok(RoundingMode::HalfEven),
+            other => Err(AppError::Validation(format!("Unknown rounding mode: {}", other))),
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            RoundingMode::None => "none",
+            RoundingMode::HalfUp => "half_up",
+            RoundingMode::HalfEven => "half_even",
+        }
+    }
+
+    // Rounds to 2 decimal places. Ties are detected with a small tolerance because
+    // values like 2.675 are stored as 2.67499999... in binary floating point.
+    fn round(self, value: f64) -> f64 {
+        if self == RoundingMode::None {
+            return value;
+        }
+
+        let scaled = value * 100.0;
+        let floor = scaled.floor();
+        let is_tie = (scaled - floor - 0.5).abs() < 1e-6;
+
+        let rounded = match (self, is_tie) {
+            (RoundingMode::HalfUp, true) => floor + 1.0,
+            (RoundingMode::HalfEven, true) if floor % 2.0 == 0.0 => floor,
+            (RoundingMode::HalfEven, true) => floor + 1.0,
+            _ => scaled.round(),
+        };
+        rounded / 100.0
+    }
+}
+
+fn get_rounding_mode_setting(conn: &Connection) -> Result<RoundingMode, AppError> {
+    match read_setting(conn, SETTING_ROUNDING_MODE)? {
+        Some(value) => RoundingMode::parse(&value),
+        None => Ok(RoundingMode::default()),
+    }
+}
+
+// Server-side totals: subtotal is the sum of item amounts, GST is charged at gst_rate
+// and rounded per the configured mode, and total is the discounted subtotal + GST
+// rounded the same way
+fn apply_order_totals(order: &mut Order, mode: RoundingMode) {
+    order.subtotal = order.items.iter().map(|item| item.amount).sum();
+    // The discount comes off the subtotal before GST, so GST is charged on what remains
+    let discount = match order.discount_type.as_str() {
+        DISCOUNT_FLAT => order.discount_value.min(order.subtotal),
+        DISCOUNT_PERCENT => order.subtotal * order.discount_value / 100.0,
+        _ => 0.0,
+    };
+    order.discount_amount = mode.round(discount);
+    let taxable = order.subtotal - order.discount_amount;
+    order.gst = mode.round(taxable * order.gst_rate / 100.0);
+    order.total = mode.round(taxable + order.gst);
+}
+
+fn get_total_orders_count(conn: &Connection, doc_type: Option<&str>) -> Result<u32, AppError> {
+    let count: u32 = conn.query_row(
+        "SELECT COUNT(*) FROM orders WHERE (?1 IS NULL OR doc_type = ?1)",
+        [doc_type],
+        |row| Ok(row.get(0)?),
+    ).map_err(|e| AppError::db("Failed to get count", e))?;
+    Ok(count)
+}
+
+const DEFAULT_PAGE_SIZE: u32 = 50;
+const MAX_PAGE_SIZE: u32 = 500;
+
+// Normalizes requested pagination: page defaults to 1 and page 0 is treated as page 1;
+// page size defaults to DEFAULT_PAGE_SIZE and is clamped to 1..=MAX_PAGE_SIZE.
+// Responses echo the clamped values so the UI knows what was actually returned.
+fn clamp_pagination(page: Option<u32>, page_size: Option<u32>) -> (u32, u32) {
+    let page = page.unwrap_or(1).max(1);
+    let page_size = page_size.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    (page, page_size)
+}
+
+// Rows to skip for a 1-based page. Worked out in 64 bits and saturated, so an absurd page
+// number from the frontend just yields an empty page instead of overflowing.
+fn page_offset(page: u32, page_size: u32) -> i64 {
+    let offset = u64::from(page.max(1) - 1).saturating_mul(u64::from(page_size));
+    i64::try_from(offset).unwrap_or(i64::MAX)
+}
+
+#[derive(Serialize)]
+struct PaginatedOrders {
     orders: Vec<Order>,
     total: u32,
     page: u32,
@@ -363,145 +1619,5258 @@ struct PaginatedOrders {
     total_pages: u32,
 }
 
+#[derive(Serialize)]
+struct PaginatedOrderSummaries {
+    orders: Vec<OrderSummary>,
+    total: u32,
+    page: u32,
+    #[serde(rename = "pageSize")]
+    page_size: u32,
+    #[serde(rename = "totalPages")]
+    total_pages: u32,
+}
+
+#[tauri::command]
+fn load_orders(page: Option<u32>, page_size: Option<u32>, doc_type: Option<String>, sort_by_priority: Option<bool>, pool: tauri::State<DbPool>) -> Result<PaginatedOrders, AppError> {
+    let conn = checkout(&pool)?;
+    let (page, page_size) = clamp_pagination(page, page_size);
+    let orders = load_orders_paginated_from_db(&conn, Some(page), Some(page_size), doc_type.as_deref(), sort_by_priority.unwrap_or(false))?;
+    let total = get_total_orders_count(&conn, doc_type.as_deref())?;
+    let total_pages = (total as f64 / page_size as f64).ceil() as u32;
+    
+    Ok(PaginatedOrders {
+        orders,
+        total,
+        page,
+        page_size,
+        total_pages,
+    })
+}
+
+#[tauri::command]
+fn recent_orders(limit: Option<u32>, pool: tauri::State<DbPool>) -> Result<Vec<Order>, AppError> {
+    let limit = limit.unwrap_or(10).clamp(1, MAX_RECENT_ORDERS);
+    let conn = checkout(&pool)?;
+
+    // Items are omitted to keep the dashboard list fast
+    query_orders(
+        &conn,
+        &format!("SELECT {} FROM orders ORDER BY created_date DESC LIMIT ?1", ORDER_COLUMNS),
+        [limit],
+        false,
+    )
+}
+
+#[tauri::command]
+fn load_order_summaries(page: Option<u32>, page_size: Option<u32>, pool: tauri::State<DbPool>) -> Result<PaginatedOrderSummaries, AppError> {
+    let conn = checkout(&pool)?;
+    let (page, page_size) = clamp_pagination(page, page_size);
+    let orders = load_order_summaries_from_db(&conn, page, page_size)?;
+    let total = get_total_orders_count(&conn, None)?;
+    let total_pages = (total as f64 / page_size as f64).ceil() as u32;
+
+    Ok(PaginatedOrderSummaries {
+        orders,
+        total,
+        page,
+        page_size,
+        total_pages,
+    })
+}
+
+#[tauri::command]
+fn save_order(order: serde_json::Value, pool: tauri::State<DbPool>) -> Result<u32, AppError> {
+    ensure_writable()?;
+    let mut order: Order = serde_json::from_value(order)
+        .map_err(|e| AppError::Validation(format!("Failed to parse order: {}", e)))?;
+    order.gstin = order.gstin.trim().to_uppercase();
+    // Callers that predate gstRate only send the GST amount
+    if order.gst_rate == 0.0 && order.gst > 0.0 && order.subtotal > 0.0 {
+        order.gst_rate = (order.gst * 100.0 / order.subtotal * 100.0).round() / 100.0;
+    }
+
+    let mut conn = checkout(&pool)?;
+    apply_order_totals(&mut order, get_rounding_mode_setting(&conn)?);
+    order.validate().map_err(|errors| AppError::Validation(errors.join("; ")))?;
+    if !order_exists(&conn, &order.order_no)? {
+        ensure_order_no_format(&conn, &order.order_no)?;
+    }
+    save_order_to_db(&mut conn, &order)
+}
+
+// Stores the standard spelling of one of ORDER_STATUSES, so a status typed as "delivered"
+// doesn't end up as a column of its own on the kanban board
+#[tauri::command]
+fn update_order_status(order_no: String, status: String, pool: tauri::State<DbPool>) -> Result<(), AppError> {
+    use rusqlite::OptionalExtension;
+
+    ensure_writable()?;
+    let status = canonical_status(&status)?;
+    let mut conn = checkout(&pool)?;
+    let tx = conn.transaction().map_err(|e| AppError::db("Failed to start transaction", e))?;
+    ensure_order_unlocked(&tx, &order_no)?;
+
+    let old_status: String = tx
+        .query_row("SELECT status FROM orders WHERE order_no = ?1", [&order_no], |row| row.get(0))
+        .optional()
+        .map_err(|e| AppError::db("Failed to look up order", e))?
+        .ok_or_else(|| AppError::NotFound(format!("Order {} not found", order_no)))?;
+
+    tx.execute(
+        "UPDATE orders SET status = ?1, updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now'), version = version + 1 WHERE order_no = ?2",
+        rusqlite::params![status, order_no],
+    )
+    .map_err(|e| AppError::db("Failed to update status", e))?;
+    record_status_change(&tx, &order_no, &old_status, status)?;
+
+    tx.commit().map_err(|e| AppError::db("Failed to commit transaction", e))?;
+    Ok(())
+}
+
+#[tauri::command]
+fn delete_order(order_no: String, pool: tauri::State<DbPool>) -> Result<(), AppError> {
+    ensure_writable()?;
+    let mut conn = checkout(&pool)?;
+    let tx = conn.transaction().map_err(|e| AppError::db("Failed to start transaction", e))?;
+    ensure_order_unlocked(&tx, &order_no)?;
+    
+    // Delete order (items will be deleted automatically due to CASCADE)
+    let deleted = tx.execute(
+        "DELETE FROM orders WHERE order_no = ?1",
+        [&order_no],
+    )
+    .map_err(|e| AppError::db("Failed to delete order", e))?;
+
+    if deleted == 0 {
+        return Err(AppError::NotFound(format!("Order {} not found", order_no)));
+    }
+    record_audit(&tx, &order_no, AUDIT_DELETED, "")?;
+
+    tx.commit().map_err(|e| AppError::db("Failed to commit transaction", e))?;
+    Ok(())
+}
+
+// Quoted rupee symbol so Excel treats it as a literal in the number format
+const EXCEL_CURRENCY_FORMAT: &str = "\"₹\"#,##0.00";
+
+fn excel_header_format() -> rust_xlsxwriter::Format {
+    rust_xlsxwriter::Format::new().set_bold()
+}
+
+fn excel_currency_format() -> rust_xlsxwriter::Format {
+    rust_xlsxwriter::Format::new().set_num_format(EXCEL_CURRENCY_FORMAT)
+}
+
+// Columns available to order exports as (key, header), in the default export order.
+// Keys are the order's JSON field names, which is what the frontend refers to them by.
+const ORDER_EXPORT_COLUMNS: &[(&str, &str)] = &[
+    ("orderNo", "Order No"),
+    ("date", "Date"),
+    ("customerName", "Customer Name"),
+    ("contactPerson", "Contact Person"),
+    ("phone", "Phone"),
+    ("gstin", "GSTIN"),
+    ("status", "Status"),
+    ("machineName", "Machine Name"),
+    ("subtotal", "Subtotal"),
+    ("discountAmount", "Discount"),
+    ("gst", "GST"),
+    ("total", "Total"),
+    ("remarks", "Remarks"),
+    ("deliveryNote", "Delivery Note"),
+    ("deliveryNoteDate", "Delivery Note Date"),
+    ("buyerOrderNo", "Buyer's Order Number"),
+    ("buyerOrderDate", "Buyer's Order Date"),
+    ("createdDate", "Created Date"),
+];
+
+// Checks requested export columns against ORDER_EXPORT_COLUMNS; None means all of them
+fn resolve_export_columns(columns: Option<Vec<String>>) -> Result<Vec<(&'static str, &'static str)>, AppError> {
+    let Some(columns) = columns else {
+        return Ok(ORDER_EXPORT_COLUMNS.to_vec());
+    };
+    if columns.is_empty() {
+        return Err(AppError::Validation("Select at least one column to export".to_string()));
+    }
+
+    let mut resolved = Vec::new();
+    for column in &columns {
+        let entry = *ORDER_EXPORT_COLUMNS
+            .iter()
+            .find(|(key, _)| key == column)
+            .ok_or_else(|| AppError::Validation(format!("Unknown export column: {}", column)))?;
+        if resolved.contains(&entry) {
+            return Err(AppError::Validation(format!("Column {} is listed more than once", column)));
+        }
+        resolved.push(entry);
+    }
+    Ok(resolved)
+}
+
+// One export column's value for an order; amounts stay numeric so Excel can format them
+enum OrderCell<'a> {
+    Text(&'a str),
+    Amount(f64),
+}
+
+fn order_cell<'a>(order: &'a Order, key: &str) -> Result<OrderCell<'a>, AppError> {
+    let text = match key {
+        "orderNo" => &order.order_no,
+        "date" => &order.date,
+        "customerName" => &order.customer_name,
+        "contactPerson" => &order.contact_person,
+        "phone" => &order.phone,
+        "gstin" => &order.gstin,
+        "status" => &order.status,
+        "machineName" => &order.machine_name,
+        "remarks" => &order.remarks,
+        "deliveryNote" => &order.delivery_note,
+        "deliveryNoteDate" => &order.delivery_note_date,
+        "buyerOrderNo" => &order.buyer_order_no,
+        "buyerOrderDate" => &order.buyer_order_date,
+        "createdDate" => &order.created_date,
+        "subtotal" => return Ok(OrderCell::Amount(order.subtotal)),
+        "discountAmount" => return Ok(OrderCell::Amount(order.discount_amount)),
+        "gst" => return Ok(OrderCell::Amount(order.gst)),
+        "total" => return Ok(OrderCell::Amount(order.total)),
+        _ => return Err(AppError::Internal(format!("Unknown export column: {}", key))),
+    };
+    Ok(OrderCell::Text(text))
+}
+
+fn write_order_cell(
+    worksheet: &mut rust_xlsxwriter::Worksheet,
+    row: u32,
+    col: u16,
+    order: &Order,
+    key: &str,
+    currency_format: &rust_xlsxwriter::Format,
+) -> Result<(), AppError> {
+    match order_cell(order, key)? {
+        OrderCell::Text(text) => worksheet.write_string(row, col, text),
+        OrderCell::Amount(amount) => worksheet.write_number_with_format(row, col, amount, currency_format),
+    }
+    .map_err(|e| AppError::Internal(format!("Failed to write {}: {}", key, e)))?;
+    Ok(())
+}
+
+// Writes the standard order columns (header row + one row per order) to a worksheet
+fn write_orders_sheet(worksheet: &mut rust_xlsxwriter::Worksheet, orders: &[Order]) -> Result<(), AppError> {
+    write_orders_sheet_columns(worksheet, orders, &resolve_export_columns(None)?, None)
+}
+
+// Rows between export progress reports
+const EXPORT_PROGRESS_INTERVAL: usize = 250;
+
+// Writes the given ORDER_EXPORT_COLUMNS entries, in that order. `on_progress`, if given,
+// is called with (rows written, total rows) every EXPORT_PROGRESS_INTERVAL rows and
+// after the last one; an error from it stops the export.
+fn write_orders_sheet_columns(
+    worksheet: &mut rust_xlsxwriter::Worksheet,
+    orders: &[Order],
+    columns: &[(&str, &str)],
+    on_progress: Option<&dyn Fn(usize, usize) -> Result<(), AppError>>,
+) -> Result<(), AppError> {
+    let header_format = excel_header_format();
+    let currency_format = excel_currency_format();
+
+    for (col, (_, header)) in columns.iter().enumerate() {
+        worksheet.write_string_with_format(0, col as u16, *header, &header_format)
+            .map_err(|e| AppError::Internal(format!("Failed to write header: {}", e)))?;
+    }
+
+    for (row, order) in orders.iter().enumerate() {
+        let row_num = (row + 1) as u32;
+        for (col, (key, _)) in columns.iter().enumerate() {
+            write_order_cell(worksheet, row_num, col as u16, order, key, &currency_format)?;
+        }
+        let written = row + 1;
+        if let Some(report) = on_progress {
+            if written % EXPORT_PROGRESS_INTERVAL == 0 || written == orders.len() {
+                report(written, orders.len())?;
+            }
+        }
+    }
+
+    worksheet.autofit();
+
+    Ok(())
+}
+
+// Excel sheet names are limited to 31 characters, cannot contain []:*?/\ and must be
+// unique ignoring case. `used` holds the (lowercased) names already taken in the
+// workbook; a clash gets a " (2)", " (3)", ... suffix, shortening the name to fit.
+fn excel_sheet_name(name: &str, used: &mut std::collections::HashSet<String>) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| if "[]:*?/\\".contains(c) { '_' } else { c })
+        .collect();
+    let cleaned = if cleaned.trim().is_empty() { "Unknown".to_string() } else { cleaned };
+
+    let mut candidate: String = cleaned.chars().take(31).collect();
+    let mut n = 2;
+    while used.contains(&candidate.to_lowercase()) {
+        let suffix = format!(" ({})", n);
+        candidate = cleaned.chars().take(31 - suffix.len()).collect::<String>() + &suffix;
+        n += 1;
+    }
+    used.insert(candidate.to_lowercase());
+    candidate
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct ExportProgress {
+    written: usize,
+    total: usize,
+}
+
+// Set by cancel_export to stop the export in progress. There's one flag for the app, as
+// the UI runs one export at a time; each export clears it when it starts.
+#[derive(Default)]
+struct ExportCancel(std::sync::atomic::AtomicBool);
+
+impl ExportCancel {
+    fn check(&self) -> Result<(), AppError> {
+        if self.0.load(std::sync::atomic::Ordering::Relaxed) {
+            return Err(AppError::Cancelled("Export cancelled".to_string()));
+        }
+        Ok(())
+    }
+}
+
+#[tauri::command]
+fn cancel_export(cancel: tauri::State<ExportCancel>) {
+    cancel.0.store(true, std::sync::atomic::Ordering::Relaxed);
+}
+
+// All orders to one sheet. `columns` picks which ORDER_EXPORT_COLUMNS keys to include
+// and in what order; omitted, every column is exported. Progress goes to the calling
+// window as "export-progress" events carrying rows written and the total.
+//
+// cancel_export stops it at the next progress report or just before saving. The workbook
+// is only written to disk by the final save, so a cancelled export leaves no partial
+// file behind (and an existing file at `file_path` untouched). It runs off the main
+// thread so the progress events and a cancel_export call get through while it works.
+//
+// A `password` applies Excel's worksheet protection: the sheet can't be edited in Excel
+// without it. This is NOT encryption. The file opens and every value stays readable
+// without the password, and Excel's protection password is a weak legacy hash that
+// free tools remove in seconds. It stops accidental edits to a price list, not a
+// determined reader; to keep prices confidential, leave those columns out instead.
+#[tauri::command(async)]
+fn export_orders(
+    file_path: String,
+    columns: Option<Vec<String>>,
+    password: Option<String>,
+    window: tauri::Window,
+    cancel: tauri::State<ExportCancel>,
+    pool: tauri::State<DbPool>,
+) -> Result<(), AppError> {
+    cancel.0.store(false, std::sync::atomic::Ordering::Relaxed);
+    let columns = resolve_export_columns(columns)?;
+    let conn = checkout(&pool)?;
+    // Load all orders for export (no pagination)
+    let orders = load_orders_from_db(&conn)?;
+    cancel.check()?;
+    
+    // Use rust_xlsxwriter to create Excel file
+    use rust_xlsxwriter::*;
+    
+    let mut workbook = Workbook::new();
+    let worksheet = workbook.add_worksheet();
+    // A missed progress event only affects the progress bar, so emit errors are ignored
+    let report = |written: usize, total: usize| {
+        let _ = window.emit("export-progress", ExportProgress { written, total });
+        cancel.check()
+    };
+    write_orders_sheet_columns(worksheet, &orders, &columns, Some(&report))?;
+    if let Some(password) = password.as_deref().filter(|p| !p.is_empty()) {
+        worksheet.protect_with_password(password);
+    }
+
+    cancel.check()?;
+    workbook.save(&file_path)
+        .map_err(|e| AppError::Internal(format!("Failed to save Excel file: {}", e)))?;
+    
+    Ok(())
+}
+
+// Incremental export for syncing: only orders changed after `since` (an ISO-8601 UTC
+// timestamp like the ones stored in updated_at; a bare YYYY-MM-DD also works since the
+// comparison is on strings). Rows migrated without an updated_at fall back to
+// created_date. Returns the number of orders written.
+#[tauri::command]
+fn export_orders_since(since: String, file_path: String, pool: tauri::State<DbPool>) -> Result<u32, AppError> {
+    let since = since.trim();
+    if since.is_empty() {
+        return Err(AppError::Validation("A since timestamp is required".to_string()));
+    }
+
+    let conn = checkout(&pool)?;
+    let orders = query_orders(
+        &conn,
+        &format!(
+            "SELECT {} FROM orders WHERE COALESCE(NULLIF(updated_at, ''), created_date) > ?1 ORDER BY created_date DESC",
+            ORDER_COLUMNS
+        ),
+        [since],
+        true,
+    )?;
+
+    let mut workbook = rust_xlsxwriter::Workbook::new();
+    let worksheet = workbook.add_worksheet();
+    write_orders_sheet(worksheet, &orders)?;
+
+    workbook.save(&file_path)
+        .map_err(|e| AppError::Internal(format!("Failed to save Excel file: {}", e)))?;
+
+    Ok(orders.len() as u32)
+}
+
+#[tauri::command]
+fn export_orders_by_status_sheets(file_path: String, pool: tauri::State<DbPool>) -> Result<(), AppError> {
+    let conn = checkout(&pool)?;
+    let orders = load_orders_from_db(&conn)?;
+
+    use rust_xlsxwriter::*;
+    use std::collections::BTreeMap;
+
+    // Group orders by status, keeping the created_date DESC order within each group
+    let mut by_status: BTreeMap<String, Vec<Order>> = BTreeMap::new();
+    for order in &orders {
+        by_status.entry(order.status.clone()).or_default().push(order.clone());
+    }
+
+    let mut workbook = Workbook::new();
+
+    let mut used_names = std::collections::HashSet::new();
+
+    // "All" summary sheet up front
+    let all_sheet = workbook.add_worksheet();
+    all_sheet.set_name(excel_sheet_name("All", &mut used_names))
+        .map_err(|e| AppError::Internal(format!("Failed to name worksheet: {}", e)))?;
+    write_orders_sheet(all_sheet, &orders)?;
+
+    for (status, status_orders) in &by_status {
+        let worksheet = workbook.add_worksheet();
+        worksheet.set_name(excel_sheet_name(status, &mut used_names))
+            .map_err(|e| AppError::Internal(format!("Failed to name worksheet for status {}: {}", status, e)))?;
+        write_orders_sheet(worksheet, status_orders)?;
+    }
+
+    workbook.save(&file_path)
+        .map_err(|e| AppError::Internal(format!("Failed to save Excel file: {}", e)))?;
+
+    Ok(())
+}
+
+#[tauri::command]
+fn export_order_json(order_no: String, file_path: String, pool: tauri::State<DbPool>) -> Result<(), AppError> {
+    let conn = checkout(&pool)?;
+    let order = load_order_by_no(&conn, &order_no)?
+        .ok_or_else(|| AppError::NotFound(format!("Order {} not found", order_no)))?;
+
+    let json = serde_json::to_string_pretty(&order)
+        .map_err(|e| AppError::Internal(format!("Failed to serialize order: {}", e)))?;
+    std::fs::write(&file_path, json)
+        .map_err(|e| AppError::Internal(format!("Failed to save JSON file: {}", e)))?;
+
+    Ok(())
+}
+
+#[tauri::command]
+fn save_order_html(file_path: String, content: String) -> Result<(), AppError> {
+    std::fs::write(&file_path, content)
+        .map_err(|e| AppError::Internal(format!("Failed to save HTML file: {}", e)))?;
+    Ok(())
+}
+
+#[derive(Debug, Serialize)]
+struct MonthCount {
+    month: String,
+    count: u32,
+    total: f64,
+}
+
+// Orders per calendar month. `date` is stored as the YYYY-MM-DD string produced by the
+// order form's date input, so the month is its first 7 characters; rows whose date
+// doesn't start with a YYYY-MM prefix are skipped rather than grouped under garbage keys.
+// Quotations are excluded.
+#[tauri::command]
+fn orders_by_month(year: Option<i32>, pool: tauri::State<DbPool>) -> Result<Vec<MonthCount>, AppError> {
+    let conn = checkout(&pool)?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT substr(date, 1, 7) AS month, COUNT(*), COALESCE(SUM(total), 0)
+             FROM orders
+             WHERE date GLOB '[0-9][0-9][0-9][0-9]-[0-9][0-9]*'
+               AND doc_type = 'order'
+               AND (?1 IS NULL OR substr(date, 1, 4) = printf('%04d', ?1))
+             GROUP BY month
+             ORDER BY month",
+        )
+        .map_err(|e| AppError::db("Failed to prepare query", e))?;
+
+    let month_iter = stmt
+        .query_map([year], |row| {
+            Ok(MonthCount {
+                month: row.get(0)?,
+                count: row.get(1)?,
+                total: row.get(2)?,
+            })
+        })
+        .map_err(|e| AppError::db("Failed to query monthly counts", e))?;
+
+    let mut months = Vec::new();
+    for month_result in month_iter {
+        months.push(month_result.map_err(|e| AppError::db("Failed to parse monthly count", e))?);
+    }
+
+    Ok(months)
+}
+
+#[derive(Debug, Serialize)]
+struct Attachment {
+    id: i64,
+    #[serde(rename = "orderNo")]
+    order_no: String,
+    #[serde(rename = "filePath")]
+    file_path: String,
+    label: String,
+    #[serde(rename = "addedAt")]
+    added_at: String,
+}
+
+fn order_exists(conn: &Connection, order_no: &str) -> Result<bool, AppError> {
+    let count: u32 = conn.query_row(
+        "SELECT COUNT(*) FROM orders WHERE order_no = ?1",
+        [order_no],
+        |row| row.get(0),
+    ).map_err(|e| AppError::db("Failed to look up order", e))?;
+    Ok(count > 0)
+}
+
+// Locked (finalized) orders can't be edited, re-statused, renumbered, repriced or deleted;
+// payments and attachments can still be recorded against them. An unknown order number
+// counts as unlocked so callers still report NotFound themselves.
+fn order_is_locked(conn: &Connection, order_no: &str) -> Result<bool, AppError> {
+    use rusqlite::OptionalExtension;
+
+    let locked: Option<bool> = conn
+        .query_row("SELECT locked FROM orders WHERE order_no = ?1", [order_no], |row| row.get(0))
+        .optional()
+        .map_err(|e| AppError::db("Failed to check order lock", e))?;
+    Ok(locked.unwrap_or(false))
+}
+
+fn ensure_order_unlocked(conn: &Connection, order_no: &str) -> Result<(), AppError> {
+    if order_is_locked(conn, order_no)? {
+        return Err(AppError::Locked(format!("Order {} is locked; unlock it before making changes", order_no)));
+    }
+    Ok(())
+}
+
+const AUDIT_CREATED: &str = "created";
+const AUDIT_UPDATED: &str = "updated";
+const AUDIT_STATUS_CHANGED: &str = "status_changed";
+const AUDIT_CONVERTED: &str = "converted";
+const AUDIT_RENAMED: &str = "renamed";
+const AUDIT_MERGED: &str = "merged";
+const AUDIT_ARCHIVED: &str = "archived";
+const AUDIT_RESTORED: &str = "restored";
+const AUDIT_LOCKED: &str = "locked";
+const AUDIT_UNLOCKED: &str = "unlocked";
+const AUDIT_DELETED: &str = "deleted";
+// Amounts and totals rebuilt from the items, by repair_amounts or recompute_order_totals
+const AUDIT_RECALCULATED: &str = "recalculated";
+const AUDIT_REPRICED: &str = "repriced";
+const AUDIT_MACHINE_CHANGED: &str = "machine_changed";
+const AUDIT_RENUMBERED: &str = "renumbered";
+const AUDIT_PAYMENT_ADDED: &str = "payment_added";
+const AUDIT_PAYMENT_REMOVED: &str = "payment_removed";
+
+// Separates the old and new status in a status_changed entry's details
+const AUDIT_STATUS_ARROW: &str = " -> ";
+
+// Appends an audit_log entry stamped in the same UTC format as updated_at. Callers pass
+// the transaction making the change, so the entry commits or rolls back with it.
+fn record_audit(conn: &Connection, order_no: &str, action: &str, details: &str) -> Result<(), AppError> {
+    conn.execute(
+        "INSERT INTO audit_log (order_no, action, changed_at, details) VALUES (?1, ?2, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'), ?3)",
+        [order_no, action, details],
+    ).map_err(|e| AppError::db("Failed to record audit entry", e))?;
+    Ok(())
+}
+
+fn record_status_change(conn: &Connection, order_no: &str, old_status: &str, new_status: &str) -> Result<(), AppError> {
+    if old_status == new_status {
+        return Ok(());
+    }
+    record_audit(conn, order_no, AUDIT_STATUS_CHANGED, &format!("{}{}{}", old_status, AUDIT_STATUS_ARROW, new_status))
+}
+
+// Only the path is stored; the drawing/photo itself stays where it is on disk
+#[tauri::command]
+fn add_attachment(order_no: String, file_path: String, label: Option<String>, pool: tauri::State<DbPool>) -> Result<Attachment, AppError> {
+    ensure_writable()?;
+    if file_path.trim().is_empty() {
+        return Err(AppError::Validation("Attachment file path is required".to_string()));
+    }
+
+    let conn = checkout(&pool)?;
+    if !order_exists(&conn, &order_no)? {
+        return Err(AppError::NotFound(format!("Order {} not found", order_no)));
+    }
+
+    let label = label.unwrap_or_default();
+    conn.execute(
+        "INSERT INTO order_attachments (order_no, file_path, label, added_at)
+         VALUES (?1, ?2, ?3, strftime('%Y-%m-%dT%H:%M:%SZ', 'now'))",
+        rusqlite::params![order_no, file_path, label],
+    )
+    .map_err(|e| AppError::db("Failed to add attachment", e))?;
+
+    let id = conn.last_insert_rowid();
+    conn.query_row(
+        "SELECT id, order_no, file_path, label, added_at FROM order_attachments WHERE id = ?1",
+        [id],
+        attachment_from_row,
+    )
+    .map_err(|e| AppError::db("Failed to load attachment", e))
+}
+
+fn attachment_from_row(row: &rusqlite::Row) -> SqlResult<Attachment> {
+    Ok(Attachment {
+        id: row.get(0)?,
+        order_no: row.get(1)?,
+        file_path: row.get(2)?,
+        label: row.get(3).unwrap_or_default(),
+        added_at: row.get(4)?,
+    })
+}
+
+#[tauri::command]
+fn list_attachments(order_no: String, pool: tauri::State<DbPool>) -> Result<Vec<Attachment>, AppError> {
+    let conn = checkout(&pool)?;
+
+    let mut stmt = conn
+        .prepare("SELECT id, order_no, file_path, label, added_at FROM order_attachments WHERE order_no = ?1 ORDER BY id")
+        .map_err(|e| AppError::db("Failed to prepare attachments query", e))?;
+
+    let attachment_iter = stmt
+        .query_map([&order_no], attachment_from_row)
+        .map_err(|e| AppError::db("Failed to query attachments", e))?;
+
+    let mut attachments = Vec::new();
+    for attachment_result in attachment_iter {
+        attachments.push(attachment_result.map_err(|e| AppError::db("Failed to parse attachment", e))?);
+    }
+
+    Ok(attachments)
+}
+
+#[tauri::command]
+fn remove_attachment(id: i64, pool: tauri::State<DbPool>) -> Result<(), AppError> {
+    ensure_writable()?;
+    let conn = checkout(&pool)?;
+
+    let deleted = conn.execute("DELETE FROM order_attachments WHERE id = ?1", [id])
+        .map_err(|e| AppError::db("Failed to remove attachment", e))?;
+
+    if deleted == 0 {
+        return Err(AppError::NotFound(format!("Attachment {} not found", id)));
+    }
+
+    Ok(())
+}
+
+// Next free line-item number for an order, so the UI doesn't have to guess and collide
+#[tauri::command]
+fn next_item_sl_no(order_no: String, pool: tauri::State<DbPool>) -> Result<u32, AppError> {
+    let conn = checkout(&pool)?;
+    let next: u32 = conn.query_row(
+        "SELECT COALESCE(MAX(sl_no), 0) + 1 FROM order_items WHERE order_no = ?1",
+        [order_no],
+        |row| row.get(0),
+    ).map_err(|e| AppError::db("Failed to get next sl_no", e))?;
+    Ok(next)
+}
+
+#[derive(Debug, Serialize)]
+struct ImportRowError {
+    row: usize,
+    #[serde(rename = "orderNo")]
+    order_no: String,
+    errors: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ImportPreview {
+    #[serde(rename = "totalRecords")]
+    total_records: usize,
+    #[serde(rename = "wouldInsert")]
+    would_insert: usize,
+    duplicates: usize,
+    #[serde(rename = "duplicateOrderNos")]
+    duplicate_order_nos: Vec<String>,
+    errors: Vec<ImportRowError>,
+}
+
+// Reads an import file into raw JSON records. Accepts a JSON array of orders (the shape
+// written by the JSON exports) or a single order object.
+fn read_import_records(file_path: &str) -> Result<Vec<serde_json::Value>, AppError> {
+    let is_json = std::path::Path::new(file_path)
+        .extension()
+        .map(|ext| ext.eq_ignore_ascii_case("json"))
+        .unwrap_or(false);
+    if !is_json {
+        return Err(AppError::Validation("Only JSON import files are supported".to_string()));
+    }
+
+    let content = std::fs::read_to_string(file_path)
+        .map_err(|e| AppError::Internal(format!("Failed to read import file: {}", e)))?;
+    let value: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| AppError::Validation(format!("Import file is not valid JSON: {}", e)))?;
+
+    match value {
+        serde_json::Value::Array(records) => Ok(records),
+        record @ serde_json::Value::Object(_) => Ok(vec![record]),
+        _ => Err(AppError::Validation("Import file must contain an order or an array of orders".to_string())),
+    }
+}
+
+// Dry run of an import: parses and validates every record and checks for order numbers
+// that already exist (or repeat within the file), without writing anything
+#[tauri::command]
+fn validate_import(file_path: String, pool: tauri::State<DbPool>) -> Result<ImportPreview, AppError> {
+    let records = read_import_records(&file_path)?;
+    let conn = checkout(&pool)?;
+
+    let mut seen = std::collections::HashSet::new();
+    let mut preview = ImportPreview {
+        total_records: records.len(),
+        would_insert: 0,
+        duplicates: 0,
+        duplicate_order_nos: Vec::new(),
+        errors: Vec::new(),
+    };
+
+    for (index, record) in records.into_iter().enumerate() {
+        let row = index + 1;
+        let order: Order = match serde_json::from_value(record.clone()) {
+            Ok(order) => order,
+            Err(e) => {
+                preview.errors.push(ImportRowError {
+                    row,
+                    order_no: record.get("orderNo").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+                    errors: vec![format!("Failed to parse order: {}", e)],
+                });
+                continue;
+            }
+        };
+
+        if let Err(errors) = order.validate() {
+            preview.errors.push(ImportRowError {
+                row,
+                order_no: order.order_no.clone(),
+                errors,
+            });
+            continue;
+        }
+
+        if !seen.insert(order.order_no.clone()) || order_exists(&conn, &order.order_no)? {
+            preview.duplicates += 1;
+            preview.duplicate_order_nos.push(order.order_no);
+            continue;
+        }
+
+        preview.would_insert += 1;
+    }
+
+    Ok(preview)
+}
+
+// Full order history for one customer, newest first. With case_insensitive the match
+// also ignores surrounding whitespace, since names are typed inconsistently.
+#[tauri::command]
+fn orders_for_customer(customer_name: String, page: Option<u32>, page_size: Option<u32>, case_insensitive: Option<bool>, pool: tauri::State<DbPool>) -> Result<PaginatedOrders, AppError> {
+    let (page, page_size) = clamp_pagination(page, page_size);
+    let offset = page_offset(page, page_size);
+    let conn = checkout(&pool)?;
+
+    let condition = if case_insensitive.unwrap_or(false) {
+        "TRIM(customer_name) = TRIM(?1) COLLATE NOCASE"
+    } else {
+        "customer_name = ?1"
+    };
+
+    let total: u32 = conn.query_row(
+        &format!("SELECT COUNT(*) FROM orders WHERE {}", condition),
+        [&customer_name],
+        |row| row.get(0),
+    ).map_err(|e| AppError::db("Failed to get count", e))?;
+
+    let orders = query_orders(
+        &conn,
+        &format!("SELECT {} FROM orders WHERE {} ORDER BY date DESC LIMIT ?2 OFFSET ?3", ORDER_COLUMNS, condition),
+        rusqlite::params![customer_name, page_size, offset],
+        true,
+    )?;
+    let total_pages = (total as f64 / page_size as f64).ceil() as u32;
+
+    Ok(PaginatedOrders {
+        orders,
+        total,
+        page,
+        page_size,
+        total_pages,
+    })
+}
+
+const ONES: [&str; 20] = [
+    "Zero", "One", "Two", "Three", "Four", "Five", "Six", "Seven", "Eight", "Nine", "Ten",
+    "Eleven", "Twelve", "Thirteen", "Fourteen", "Fifteen", "Sixteen", "Seventeen", "Eighteen", "Nineteen",
+];
+const TENS: [&str; 10] = [
+    "", "", "Twenty", "Thirty", "Forty", "Fifty", "Sixty", "Seventy", "Eighty", "Ninety",
+];
+
+// Words for 1..=99
+fn two_digit_words(n: u64) -> String {
+    if n < 20 {
+        ONES[n as usize].to_string()
+    } else if n.is_multiple_of(10) {
+        TENS[(n / 10) as usize].to_string()
+    } else {
+        format!("{} {}", TENS[(n / 10) as usize], ONES[(n % 10) as usize])
+    }
+}
+
+// Words for 1..=999
+fn three_digit_words(n: u64) -> String {
+    match (n / 100, n % 100) {
+        (0, rest) => two_digit_words(rest),
+        (hundreds, 0) => format!("{} Hundred", ONES[hundreds as usize]),
+        (hundreds, rest) => format!("{} Hundred {}", ONES[hundreds as usize], two_digit_words(rest)),
+    }
+}
+
+// Indian numbering system: thousand, lakh (1,00,000) and crore (1,00,00,000).
+// Amounts of 100 crore and above repeat the scale, e.g. "One Hundred Crore".
+fn indian_number_words(n: u64) -> String {
+    if n == 0 {
+        return ONES[0].to_string();
+    }
+
+    let crore = n / 10_000_000;
+    let lakh = (n / 100_000) % 100;
+    let thousand = (n / 1_000) % 100;
+    let rest = n % 1_000;
+
+    let mut parts = Vec::new();
+    if crore > 0 {
+        parts.push(format!("{} Crore", indian_number_words(crore)));
+    }
+    if lakh > 0 {
+        parts.push(format!("{} Lakh", two_digit_words(lakh)));
+    }
+    if thousand > 0 {
+        parts.push(format!("{} Thousand", two_digit_words(thousand)));
+    }
+    if rest > 0 {
+        parts.push(three_digit_words(rest));
+    }
+    parts.join(" ")
+}
+
+// e.g. 1200.50 -> "Rupees One Thousand Two Hundred and Fifty Paise Only"
+fn amount_to_words(amount: f64) -> Result<String, AppError> {
+    if !amount.is_finite() {
+        return Err(AppError::Validation("Amount must be a number".to_string()));
+    }
+    if amount < 0.0 {
+        return Err(AppError::Validation("Amount cannot be negative".to_string()));
+    }
+
+    // Work in whole paise so float error can't leak into the paise part
+    let total_paise = (amount * 100.0).round() as u64;
+    let rupees = total_paise / 100;
+    let paise = total_paise % 100;
+
+    if paise == 0 {
+        Ok(format!("Rupees {} Only", indian_number_words(rupees)))
+    } else {
+        Ok(format!("Rupees {} and {} Paise Only", indian_number_words(rupees), two_digit_words(paise)))
+    }
+}
+
+#[tauri::command]
+fn amount_in_words(amount: f64) -> Result<String, AppError> {
+    amount_to_words(amount)
+}
+
+// Creates a new database with the current schema (all migrations applied) and no rows
+#[tauri::command]
+fn create_empty_database(path: String) -> Result<(), AppError> {
+    let db_path = std::path::Path::new(&path);
+    if db_path.exists() {
+        return Err(AppError::Conflict(format!("{} already exists", path)));
+    }
+
+    open_database(db_path).map_err(|e| AppError::db("Failed to create database", e))?;
+    Ok(())
+}
+
+// Items for one order, for lazy-loading the detail view alongside load_order_summaries
+#[tauri::command]
+fn load_order_items(order_no: String, pool: tauri::State<DbPool>) -> Result<Vec<OrderItem>, AppError> {
+    let conn = checkout(&pool)?;
+    if !order_exists(&conn, &order_no)? {
+        return Err(AppError::NotFound(format!("Order {} not found", order_no)));
+    }
+    load_items_for_order(&conn, &order_no)
+}
+
+// Token the caller must pass to delete_all_orders, guarding against accidental invocation
+const DELETE_ALL_CONFIRMATION: &str = "DELETE ALL";
+
+#[tauri::command]
+fn delete_all_orders(confirm: String, pool: tauri::State<DbPool>) -> Result<u32, AppError> {
+    ensure_writable()?;
+    if confirm != DELETE_ALL_CONFIRMATION {
+        return Err(AppError::Validation(format!("Type \"{}\" to confirm deleting all orders", DELETE_ALL_CONFIRMATION)));
+    }
+
+    let mut conn = checkout(&pool)?;
+    let tx = conn.transaction().map_err(|e| AppError::db("Failed to start transaction", e))?;
+
+    let locked: u32 = tx.query_row("SELECT COUNT(*) FROM orders WHERE locked = 1", [], |row| row.get(0))
+        .map_err(|e| AppError::db("Failed to count locked orders", e))?;
+    if locked > 0 {
+        return Err(AppError::Locked(format!("{} locked order(s) must be unlocked before deleting all orders", locked)));
+    }
+
+    tx.execute(
+        "INSERT INTO audit_log (order_no, action, changed_at, details)
+         SELECT order_no, ?1, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'), 'delete all' FROM orders",
+        [AUDIT_DELETED],
+    ).map_err(|e| AppError::db("Failed to record audit entries", e))?;
+
+    // Items and attachments go with their orders via ON DELETE CASCADE
+    let deleted = tx.execute("DELETE FROM orders", [])
+        .map_err(|e| AppError::db("Failed to delete orders", e))?;
+
+    tx.commit().map_err(|e| AppError::db("Failed to commit transaction", e))?;
+
+    Ok(deleted as u32)
+}
+
+#[derive(Debug, Serialize)]
+struct StatusCount {
+    status: String,
+    count: u32,
+    total: f64,
+}
+
+#[derive(Debug, Serialize)]
+struct SummaryReport {
+    from: String,
+    to: String,
+    #[serde(rename = "totalOrders")]
+    total_orders: u32,
+    subtotal: f64,
+    discounts: f64,
+    #[serde(rename = "gstCollected")]
+    gst_collected: f64,
+    #[serde(rename = "totalRevenue")]
+    total_revenue: f64,
+    #[serde(rename = "statusBreakdown")]
+    status_breakdown: Vec<StatusCount>,
+}
+
+// Aggregates for orders whose date falls in [from, to] (inclusive, YYYY-MM-DD).
+// Quotations are left out so revenue only reflects confirmed orders.
+fn summary_between(conn: &Connection, from: &str, to: &str) -> Result<SummaryReport, AppError> {
+    let (total_orders, subtotal, discounts, gst_collected, total_revenue) = conn.query_row(
+        "SELECT COUNT(*), COALESCE(SUM(subtotal), 0), COALESCE(SUM(discount_amount), 0), COALESCE(SUM(gst), 0), COALESCE(SUM(total), 0)
+         FROM orders WHERE date >= ?1 AND date <= ?2 AND doc_type = 'order'",
+        [from, to],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)),
+    ).map_err(|e| AppError::db("Failed to compute summary", e))?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT status, COUNT(*), COALESCE(SUM(total), 0) FROM orders
+             WHERE date >= ?1 AND date <= ?2 AND doc_type = 'order'
+             GROUP BY status ORDER BY status",
+        )
+        .map_err(|e| AppError::db("Failed to prepare status breakdown", e))?;
+
+    let status_iter = stmt
+        .query_map([from, to], |row| {
+            Ok(StatusCount {
+                status: row.get(0)?,
+                count: row.get(1)?,
+                total: row.get(2)?,
+            })
+        })
+        .map_err(|e| AppError::db("Failed to query status breakdown", e))?;
+
+    let mut status_breakdown = Vec::new();
+    for status_result in status_iter {
+        status_breakdown.push(status_result.map_err(|e| AppError::db("Failed to parse status breakdown", e))?);
+    }
+
+    Ok(SummaryReport {
+        from: from.to_string(),
+        to: to.to_string(),
+        total_orders,
+        subtotal,
+        discounts,
+        gst_collected,
+        total_revenue,
+        status_breakdown,
+    })
+}
+
+#[tauri::command]
+fn summary_report(from: String, to: String, pool: tauri::State<DbPool>) -> Result<SummaryReport, AppError> {
+    let (start, end) = parse_date_range(&from, &to)?;
+    let (from, to) = (start.to_string(), end.to_string());
+
+    let conn = checkout(&pool)?;
+    summary_between(&conn, &from, &to)
+}
+
+// Formats an amount with Indian digit grouping, e.g. 1234567.5 -> "Rs. 12,34,567.50"
+fn format_rupees(amount: f64) -> String {
+    let fixed = format!("{:.2}", amount.abs());
+    let (whole, fraction) = fixed.split_once('.').unwrap_or((&fixed, "00"));
+
+    let mut grouped = String::new();
+    let digits: Vec<char> = whole.chars().collect();
+    let head_len = digits.len().saturating_sub(3);
+    for (i, c) in digits[..head_len].iter().enumerate() {
+        if i > 0 && (head_len - i).is_multiple_of(2) {
+            grouped.push(',');
+        }
+        grouped.push(*c);
+    }
+    if head_len > 0 {
+        grouped.push(',');
+    }
+    grouped.extend(&digits[head_len..]);
+
+    let sign = if amount < 0.0 { "-" } else { "" };
+    format!("{}Rs. {}.{}", sign, grouped, fraction)
+}
+
+// Number of body lines that fit on one A4 page below the title
+const PDF_LINES_PER_PAGE: usize = 46;
+
+// Escapes a string for a PDF literal. The built-in Helvetica fonts only cover
+// WinAnsi, so anything outside ASCII is replaced rather than rendered as garbage.
+fn pdf_escape(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            c if c.is_ascii() && !c.is_ascii_control() => escaped.push(c),
+            _ => escaped.push('?'),
+        }
+    }
+    escaped
+}
+
+// Renders a plain text document (bold title + one body line per entry) as a minimal
+// PDF using the standard Helvetica fonts, paginating onto as many A4 pages as needed
+fn render_text_pdf(title: &str, lines: &[String]) -> Vec<u8> {
+    let pages: Vec<&[String]> = if lines.is_empty() {
+        vec![&[]]
+    } else {
+        lines.chunks(PDF_LINES_PER_PAGE).collect()
+    };
+
+    // Objects 1-4 are fixed (catalog, page tree, two fonts); each page adds a page and a content object
+    let mut objects: Vec<String> = Vec::new();
+    let page_ids: Vec<usize> = (0..pages.len()).map(|i| 5 + i * 2).collect();
+    let kids = page_ids.iter().map(|id| format!("{} 0 R", id)).collect::<Vec<_>>().join(" ");
+
+    objects.push("<< /Type /Catalog /Pages 2 0 R >>".to_string());
+    objects.push(format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids, pages.len()));
+    objects.push("<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>".to_string());
+    objects.push("<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>".to_string());
+
+    for (index, page_lines) in pages.iter().enumerate() {
+        let mut content = format!("BT /F2 16 Tf 50 790 Td ({}) Tj ET\n", pdf_escape(title));
+        content.push_str("BT /F1 11 Tf 50 760 Td 15 TL\n");
+        for line in page_lines.iter() {
+            content.push_str(&format!("({}) Tj T*\n", pdf_escape(line)));
+        }
+        content.push_str("ET\n");
+        if pages.len() > 1 {
+            content.push_str(&format!("BT /F1 9 Tf 500 30 Td (Page {} of {}) Tj ET\n", index + 1, pages.len()));
+        }
+
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 595 842] /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+            page_ids[index] + 1
+        ));
+        objects.push(format!("<< /Length {} >>\nstream\n{}endstream", content.len(), content));
+    }
+
+    let mut pdf = String::from("%PDF-1.4\n");
+    let mut offsets = Vec::new();
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.push_str(&format!("{} 0 obj\n{}\nendobj\n", i + 1, object));
+    }
+
+    let xref_offset = pdf.len();
+    pdf.push_str(&format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1));
+    for offset in offsets {
+        pdf.push_str(&format!("{:010} 00000 n \n", offset));
+    }
+    pdf.push_str(&format!(
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+        objects.len() + 1,
+        xref_offset
+    ));
+
+    pdf.into_bytes()
+}
+
+// One-page summary for sharing monthly numbers with people who don't use the app
+#[tauri::command]
+fn export_summary_pdf(from: String, to: String, file_path: String, pool: tauri::State<DbPool>) -> Result<(), AppError> {
+    let (start, end) = parse_date_range(&from, &to)?;
+    let (from, to) = (start.to_string(), end.to_string());
+
+    let conn = checkout(&pool)?;
+    let summary = summary_between(&conn, &from, &to)?;
+
+    let mut lines = vec![
+        format!("Period: {} to {}", summary.from, summary.to),
+        String::new(),
+        format!("Total orders: {}", summary.total_orders),
+        format!("Gross value: {}", format_rupees(summary.subtotal)),
+        format!("Discounts: {}", format_rupees(summary.discounts)),
+        format!("Taxable value: {}", format_rupees(summary.subtotal - summary.discounts)),
+        format!("GST collected: {}", format_rupees(summary.gst_collected)),
+        format!("Total revenue: {}", format_rupees(summary.total_revenue)),
+        String::new(),
+        "Status breakdown".to_string(),
+    ];
+    for status in &summary.status_breakdown {
+        lines.push(format!("    {}: {} orders, {}", status.status, status.count, format_rupees(status.total)));
+    }
+
+    let company = load_company_profile(&conn)?;
+    let pdf = render_text_pdf(&format!("{} - Order Summary", company.name), &lines);
+    std::fs::write(&file_path, pdf)
+        .map_err(|e| AppError::Internal(format!("Failed to save PDF file: {}", e)))?;
+
+    Ok(())
+}
+
+// Changes an order's number along with every row that references it. Foreign keys are
+// deferred to commit so the parent and child rows can be updated in any order.
+fn rename_order_number(tx: &rusqlite::Transaction, old_no: &str, new_no: &str) -> Result<(), AppError> {
+    if new_no.trim().is_empty() {
+        return Err(AppError::Validation("New order number is required".to_string()));
+    }
+    if order_exists(tx, new_no)? {
+        return Err(AppError::Conflict(format!("Order {} already exists", new_no)));
+    }
+
+    tx.execute_batch("PRAGMA defer_foreign_keys = ON")
+        .map_err(|e| AppError::db("Failed to defer foreign keys", e))?;
+
+    let renamed = tx.execute(
+        "UPDATE orders SET order_no = ?1 WHERE order_no = ?2",
+        [new_no, old_no],
+    ).map_err(|e| AppError::db("Failed to rename order", e))?;
+    if renamed == 0 {
+        return Err(AppError::NotFound(format!("Order {} not found", old_no)));
+    }
+
+    tx.execute(
+        "UPDATE order_items SET order_no = ?1 WHERE order_no = ?2",
+        [new_no, old_no],
+    ).map_err(|e| AppError::db("Failed to update order items", e))?;
+    tx.execute(
+        "UPDATE order_attachments SET order_no = ?1 WHERE order_no = ?2",
+        [new_no, old_no],
+    ).map_err(|e| AppError::db("Failed to update attachments", e))?;
+    tx.execute(
+        "UPDATE payments SET order_no = ?1 WHERE order_no = ?2",
+        [new_no, old_no],
+    ).map_err(|e| AppError::db("Failed to update payments", e))?;
+    // History follows the order so it reads as one timeline under the new number
+    tx.execute(
+        "UPDATE audit_log SET order_no = ?1 WHERE order_no = ?2",
+        [new_no, old_no],
+    ).map_err(|e| AppError::db("Failed to update audit log", e))?;
+
+    Ok(())
+}
+
+// Promotes a quotation to an order: flips doc_type, optionally renumbers it, stamps
+// today's date and resets the status to "New" (the app's pending/not-started status)
+#[tauri::command]
+fn convert_quotation(order_no: String, new_order_no: Option<String>, pool: tauri::State<DbPool>) -> Result<Order, AppError> {
+    ensure_writable()?;
+    let mut conn = checkout(&pool)?;
+    let tx = conn.transaction().map_err(|e| AppError::db("Failed to start transaction", e))?;
+
+    let order = load_order_by_no(&tx, &order_no)?
+        .ok_or_else(|| AppError::NotFound(format!("Order {} not found", order_no)))?;
+    if order.doc_type != DOC_TYPE_QUOTATION {
+        return Err(AppError::Validation(format!("{} is not a quotation", order_no)));
+    }
+    ensure_order_unlocked(&tx, &order_no)?;
+
+    let final_no = match new_order_no {
+        Some(new_no) if new_no != order_no => {
+            ensure_order_no_format(&tx, &new_no)?;
+            rename_order_number(&tx, &order_no, &new_no)?;
+            new_no
+        }
+        _ => order_no,
+    };
+
+    tx.execute(
+        "UPDATE orders SET doc_type = ?1, status = 'New', date = date('now', 'localtime'), printed_at = NULL, updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now'), version = version + 1 WHERE order_no = ?2",
+        [DOC_TYPE_ORDER, final_no.as_str()],
+    ).map_err(|e| AppError::db("Failed to convert quotation", e))?;
+    let details = if final_no == order.order_no { String::new() } else { format!("from quotation {}", order.order_no) };
+    record_audit(&tx, &final_no, AUDIT_CONVERTED, &details)?;
+    stamp_invoice_hash(&tx, &final_no)?;
+    record_status_change(&tx, &final_no, &order.status, "New")?;
+
+    let converted = load_order_by_no(&tx, &final_no)?
+        .ok_or_else(|| AppError::NotFound(format!("Order {} not found", final_no)))?;
+
+    tx.commit().map_err(|e| AppError::db("Failed to commit transaction", e))?;
+
+    Ok(converted)
+}
+
+#[tauri::command]
+fn get_rounding_mode(pool: tauri::State<DbPool>) -> Result<RoundingMode, AppError> {
+    let conn = checkout(&pool)?;
+    get_rounding_mode_setting(&conn)
+}
+
+#[tauri::command]
+fn set_rounding_mode(mode: RoundingMode, pool: tauri::State<DbPool>) -> Result<(), AppError> {
+    ensure_writable()?;
+    let conn = checkout(&pool)?;
+    write_setting(&conn, SETTING_ROUNDING_MODE, mode.as_str())
+}
+
+#[tauri::command]
+fn get_setting(key: String, pool: tauri::State<DbPool>) -> Result<Option<String>, AppError> {
+    let conn = checkout(&pool)?;
+    read_setting(&conn, &key)
+}
+
+#[tauri::command]
+fn set_setting(key: String, value: String, pool: tauri::State<DbPool>) -> Result<(), AppError> {
+    ensure_writable()?;
+    if key.trim().is_empty() {
+        return Err(AppError::Validation("Setting key is required".to_string()));
+    }
+    validate_setting(&key, &value)?;
+
+    let conn = checkout(&pool)?;
+    write_setting(&conn, &key, &value)
+}
+
+#[tauri::command]
+fn get_gst_rate(pool: tauri::State<DbPool>) -> Result<f64, AppError> {
+    let conn = checkout(&pool)?;
+    get_gst_rate_setting(&conn)
+}
+
+#[tauri::command]
+fn get_order_prefix(pool: tauri::State<DbPool>) -> Result<String, AppError> {
+    let conn = checkout(&pool)?;
+    get_order_prefix_setting(&conn)
+}
+
+// Letterhead details used on generated invoices and reports
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct CompanyProfile {
+    name: String,
+    #[serde(default)]
+    tagline: String,
+    #[serde(default)]
+    address: String,
+    #[serde(default)]
+    gstin: String,
+    #[serde(default)]
+    phone: String,
+    #[serde(default)]
+    email: String,
+    #[serde(rename = "logoPath", default)]
+    logo_path: String,
+}
+
+impl Default for CompanyProfile {
+    // The letterhead the app shipped with, used until a profile is saved
+    fn default() -> Self {
+        CompanyProfile {
+            name: "AAKSO RUBBER WORKS".to_string(),
+            tagline: "Mfg: Rubber Extruded, Moulded & Lining Products".to_string(),
+            address: "Admn. Off/Works: D-34, Phase V, IDA, Jeedimetla, Hyderabad-500 055".to_string(),
+            gstin: String::new(),
+            phone: "9440624313, 9550884200".to_string(),
+            email: "aaksorubber@gmail.com".to_string(),
+            logo_path: String::new(),
+        }
+    }
+}
+
+fn load_company_profile(conn: &Connection) -> Result<CompanyProfile, AppError> {
+    use rusqlite::OptionalExtension;
+
+    let profile = conn
+        .query_row(
+            "SELECT name, tagline, address, gstin, phone, email, logo_path FROM company_profile WHERE id = 1",
+            [],
+            |row| {
+                Ok(CompanyProfile {
+                    name: row.get(0)?,
+                    tagline: row.get(1).unwrap_or_default(),
+                    address: row.get(2).unwrap_or_default(),
+                    gstin: row.get(3).unwrap_or_default(),
+                    phone: row.get(4).unwrap_or_default(),
+                    email: row.get(5).unwrap_or_default(),
+                    logo_path: row.get(6).unwrap_or_default(),
+                })
+            },
+        )
+        .optional()
+        .map_err(|e| AppError::db("Failed to load company profile", e))?;
+
+    Ok(profile.unwrap_or_default())
+}
+
+#[tauri::command]
+fn get_company_profile(pool: tauri::State<DbPool>) -> Result<CompanyProfile, AppError> {
+    let conn = checkout(&pool)?;
+    load_company_profile(&conn)
+}
+
+#[tauri::command]
+fn set_company_profile(profile: CompanyProfile, pool: tauri::State<DbPool>) -> Result<(), AppError> {
+    ensure_writable()?;
+    if profile.name.trim().is_empty() {
+        return Err(AppError::Validation("Company name is required".to_string()));
+    }
+    let gstin = profile.gstin.trim().to_uppercase();
+    if !gstin.is_empty() {
+        validate_gstin(&gstin).map_err(AppError::Validation)?;
+    }
+
+    let conn = checkout(&pool)?;
+    conn.execute(
+        "INSERT INTO company_profile (id, name, tagline, address, gstin, phone, email, logo_path)
+         VALUES (1, ?1, ?2, ?3, ?4, ?5, ?6, ?7)
+         ON CONFLICT(id) DO UPDATE SET
+            name = excluded.name,
+            tagline = excluded.tagline,
+            address = excluded.address,
+            gstin = excluded.gstin,
+            phone = excluded.phone,
+            email = excluded.email,
+            logo_path = excluded.logo_path",
+        rusqlite::params![
+            profile.name.trim(),
+            profile.tagline,
+            profile.address,
+            gstin,
+            profile.phone,
+            profile.email,
+            profile.logo_path
+        ],
+    )
+    .map_err(|e| AppError::db("Failed to save company profile", e))?;
+
+    Ok(())
+}
+
+#[derive(Debug, Serialize)]
+struct ItemMatch {
+    #[serde(flatten)]
+    item: OrderItem,
+    #[serde(rename = "orderNo")]
+    order_no: String,
+    #[serde(rename = "customerName")]
+    customer_name: String,
+    date: String,
+    #[serde(rename = "docType")]
+    doc_type: String,
+}
+
+// Past items with the given dimensions, newest first, for pricing repeat work. Each
+// filter is optional and compared trimmed and case-insensitively since dia/shore are
+// typed free-hand ("25mm" vs " 25MM"). Quotations are included: a quoted rate is
+// still a useful reference. At least one filter is required so this can't dump
+// every item ever sold.
+#[tauri::command]
+fn find_items(dia: Option<String>, shore: Option<String>, item_type: Option<String>, pool: tauri::State<DbPool>) -> Result<Vec<ItemMatch>, AppError> {
+    let normalize = |value: Option<String>| value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+    let (dia, shore, item_type) = (normalize(dia), normalize(shore), normalize(item_type));
+    if dia.is_none() && shore.is_none() && item_type.is_none() {
+        return Err(AppError::Validation("Specify at least one of dia, shore or item type".to_string()));
+    }
+
+    let conn = checkout(&pool)?;
+    let columns = prefixed_item_columns("i");
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {}, o.order_no, o.customer_name, o.date, o.doc_type
+             FROM order_items i
+             JOIN orders o ON o.order_no = i.order_no
+             WHERE (?1 IS NULL OR TRIM(i.dia) = ?1 COLLATE NOCASE)
+               AND (?2 IS NULL OR TRIM(i.shore) = ?2 COLLATE NOCASE)
+               AND (?3 IS NULL OR TRIM(i.item_type) = ?3 COLLATE NOCASE)
+             ORDER BY o.date DESC, o.order_no, i.sl_no
+             LIMIT ?4",
+            columns
+        ))
+        .map_err(|e| AppError::db("Failed to prepare item search", e))?;
+
+    let match_iter = stmt
+        .query_map(rusqlite::params![dia, shore, item_type, MAX_PAGE_SIZE], |row| {
+            Ok(ItemMatch {
+                item: item_from_row(row)?,
+                order_no: row.get(10)?,
+                customer_name: row.get(11)?,
+                date: row.get(12)?,
+                doc_type: row.get(13)?,
+            })
+        })
+        .map_err(|e| AppError::db("Failed to search items", e))?;
+
+    let mut matches = Vec::new();
+    for match_result in match_iter {
+        matches.push(match_result.map_err(|e| AppError::db("Failed to parse item match", e))?);
+    }
+    Ok(matches)
+}
+
+// Tolerance for stored amounts vs qty * rate; anything within a paisa is float noise
+const AMOUNT_TOLERANCE: f64 = 0.01;
+
+#[derive(Debug, Serialize)]
+struct AmountMismatch {
+    #[serde(rename = "orderNo")]
+    order_no: String,
+    #[serde(rename = "slNo")]
+    sl_no: u32,
+    qty: f64,
+    rate: f64,
+    #[serde(rename = "storedAmount")]
+    stored_amount: f64,
+    #[serde(rename = "expectedAmount")]
+    expected_amount: f64,
+}
+
+fn find_amount_mismatches(conn: &Connection) -> Result<Vec<AmountMismatch>, AppError> {
+    let mut stmt = conn
+        .prepare(&format!("SELECT {}, order_no FROM order_items ORDER BY order_no, sl_no", ITEM_COLUMNS))
+        .map_err(|e| AppError::db("Failed to prepare items query", e))?;
+
+    let item_iter = stmt
+        .query_map([], |row| {
+            let item = item_from_row(row)?;
+            let order_no: String = row.get(10)?;
+            Ok((order_no, item))
+        })
+        .map_err(|e| AppError::db("Failed to query items", e))?;
+
+    let mut mismatches = Vec::new();
+    for item_result in item_iter {
+        let (order_no, item) = item_result.map_err(|e| AppError::db("Failed to parse item", e))?;
+        let expected_amount = item.expected_amount();
+        if (item.amount - expected_amount).abs() > AMOUNT_TOLERANCE {
+            mismatches.push(AmountMismatch {
+                order_no,
+                sl_no: item.sl_no,
+                qty: item.qty,
+                rate: item.rate,
+                stored_amount: item.amount,
+                expected_amount,
+            });
+        }
+    }
+    Ok(mismatches)
+}
+
+// Items whose stored amount disagrees with qty * rate (rate alone when qty is 0),
+// left over from when amounts were only computed in the browser
+#[tauri::command]
+fn audit_amounts(pool: tauri::State<DbPool>) -> Result<Vec<AmountMismatch>, AppError> {
+    let conn = checkout(&pool)?;
+    find_amount_mismatches(&conn)
+}
+
+// Rebuilds an order's money fields from its items: each amount becomes qty * rate (see
+// OrderItem::expected_amount), then the discount and GST are reapplied as on save
+fn recalculate_order(order: &mut Order, mode: RoundingMode) {
+    for item in &mut order.items {
+        item.amount = item.expected_amount();
+    }
+    apply_order_totals(order, mode);
+}
+
+// Stores the item amounts and totals produced by recalculate_order; a changed total can
+// also change the payment status
+fn write_recalculated_order(tx: &rusqlite::Transaction, order: &Order) -> Result<(), AppError> {
+    for item in &order.items {
+        tx.execute(
+            "UPDATE order_items SET amount = ?1 WHERE order_no = ?2 AND sl_no = ?3",
+            rusqlite::params![item.amount, order.order_no, item.sl_no],
+        ).map_err(|e| AppError::db("Failed to update item amount", e))?;
+    }
+
+    tx.execute(
+        "UPDATE orders SET subtotal = ?1, discount_amount = ?2, gst = ?3, total = ?4, printed_at = NULL, updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now'), version = version + 1 WHERE order_no = ?5",
+        rusqlite::params![order.subtotal, order.discount_amount, order.gst, order.total, order.order_no],
+    ).map_err(|e| AppError::db("Failed to update order totals", e))?;
+
+    refresh_payment_status(tx, &order.order_no)?;
+    stamp_invoice_hash(tx, &order.order_no)
+}
+
+// Fixes every mismatch reported by audit_amounts and recomputes the affected orders'
+// totals, all in one transaction, with GST recomputed at each order's own gst_rate.
+// Locked orders are left as they are. Returns the corrections made.
+#[tauri::command]
+fn repair_amounts(pool: tauri::State<DbPool>) -> Result<Vec<AmountMismatch>, AppError> {
+    ensure_writable()?;
+    let mut conn = checkout(&pool)?;
+    let mode = get_rounding_mode_setting(&conn)?;
+    let tx = conn.transaction().map_err(|e| AppError::db("Failed to start transaction", e))?;
+
+    let mut mismatches = Vec::new();
+    for mismatch in find_amount_mismatches(&tx)? {
+        if !order_is_locked(&tx, &mismatch.order_no)? {
+            mismatches.push(mismatch);
+        }
+    }
+    let mut order_nos: Vec<&str> = mismatches.iter().map(|m| m.order_no.as_str()).collect();
+    order_nos.dedup();
+    for order_no in order_nos {
+        let mut order = load_order_by_no(&tx, order_no)?
+            .ok_or_else(|| AppError::NotFound(format!("Order {} not found", order_no)))?;
+        recalculate_order(&mut order, mode);
+        write_recalculated_order(&tx, &order)?;
+        record_audit(&tx, order_no, AUDIT_RECALCULATED, "repair_amounts")?;
+    }
+
+    tx.commit().map_err(|e| AppError::db("Failed to commit transaction", e))?;
+
+    Ok(mismatches)
+}
+
+const PAYMENT_UNPAID: &str = "unpaid";
+const PAYMENT_PARTIAL: &str = "partial";
+const PAYMENT_PAID: &str = "paid";
+
+// Derives payment_status from amount_paid vs total; half a paisa of slack so a
+// payment entered to two decimals settles a total that was rounded the same way
+fn refresh_payment_status(conn: &Connection, order_no: &str) -> Result<(), AppError> {
+    conn.execute(
+        "UPDATE orders SET payment_status = CASE
+             WHEN amount_paid <= 0 THEN ?1
+             WHEN amount_paid + 0.005 >= total THEN ?2
+             ELSE ?3
+         END
+         WHERE order_no = ?4",
+        [PAYMENT_UNPAID, PAYMENT_PAID, PAYMENT_PARTIAL, order_no],
+    ).map_err(|e| AppError::db("Failed to update payment status", e))?;
+    Ok(())
+}
+
+// Recomputes amount_paid from the order's payments, then its payment status
+fn refresh_amount_paid(conn: &Connection, order_no: &str) -> Result<(), AppError> {
+    conn.execute(
+        "UPDATE orders SET
+            amount_paid = (SELECT COALESCE(SUM(amount), 0) FROM payments WHERE payments.order_no = orders.order_no),
+            updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now')
+         WHERE order_no = ?1",
+        [order_no],
+    ).map_err(|e| AppError::db("Failed to update amount paid", e))?;
+    refresh_payment_status(conn, order_no)
+}
+
+#[derive(Debug, Serialize)]
+struct Outstanding {
+    #[serde(rename = "orderNo")]
+    order_no: String,
+    #[serde(rename = "customerName")]
+    customer_name: String,
+    date: String,
+    total: f64,
+    #[serde(rename = "amountPaid")]
+    amount_paid: f64,
+    balance: f64,
+    #[serde(rename = "paymentStatus")]
+    payment_status: String,
+    #[serde(rename = "daysOutstanding")]
+    days_outstanding: i64,
+    #[serde(rename = "agingBucket")]
+    aging_bucket: String,
+}
+
+fn aging_bucket(days: i64) -> &'static str {
+    match days {
+        i64::MIN..=30 => "0-30",
+        31..=60 => "31-60",
+        61..=90 => "61-90",
+        _ => "90+",
+    }
+}
+
+// Receivables: orders whose total isn't covered by amount_paid, oldest first. Quotations
+// and rejected orders are never billed, so they're left out. Age is counted from the
+// order date in local days; an unparseable date ages as 0 rather than dropping the row.
+#[tauri::command]
+fn outstanding_orders(pool: tauri::State<DbPool>) -> Result<Vec<Outstanding>, AppError> {
+    let conn = checkout(&pool)?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT order_no, customer_name, date, total, amount_paid, payment_status,
+                    COALESCE(CAST(julianday('now', 'localtime', 'start of day') - julianday(date) AS INTEGER), 0)
+             FROM orders
+             WHERE doc_type = 'order'
+               AND status != 'Rejected'
+               AND total > amount_paid + 0.005
+             ORDER BY date, order_no",
+        )
+        .map_err(|e| AppError::db("Failed to prepare query", e))?;
+
+    let outstanding_iter = stmt
+        .query_map([], |row| {
+            let total: f64 = row.get(3)?;
+            let amount_paid: f64 = row.get(4)?;
+            let days_outstanding: i64 = row.get::<_, i64>(6)?.max(0);
+            Ok(Outstanding {
+                order_no: row.get(0)?,
+                customer_name: row.get(1)?,
+                date: row.get(2)?,
+                total,
+                amount_paid,
+                balance: total - amount_paid,
+                payment_status: row.get(5)?,
+                days_outstanding,
+                aging_bucket: aging_bucket(days_outstanding).to_string(),
+            })
+        })
+        .map_err(|e| AppError::db("Failed to query outstanding orders", e))?;
+
+    let mut outstanding = Vec::new();
+    for outstanding_result in outstanding_iter {
+        outstanding.push(outstanding_result.map_err(|e| AppError::db("Failed to parse outstanding order", e))?);
+    }
+    Ok(outstanding)
+}
+
+#[derive(Debug, Serialize)]
+struct Payment {
+    id: i64,
+    #[serde(rename = "orderNo")]
+    order_no: String,
+    amount: f64,
+    #[serde(rename = "paidOn")]
+    paid_on: String,
+    method: String,
+}
+
+fn payment_from_row(row: &rusqlite::Row) -> SqlResult<Payment> {
+    Ok(Payment {
+        id: row.get(0)?,
+        order_no: row.get(1)?,
+        amount: row.get(2)?,
+        paid_on: row.get(3)?,
+        method: row.get(4).unwrap_or_default(),
+    })
+}
+
+// `paid_on` defaults to today; the order's amount_paid and payment status are updated
+// in the same transaction
+#[tauri::command]
+fn add_payment(order_no: String, amount: f64, paid_on: Option<String>, method: Option<String>, pool: tauri::State<DbPool>) -> Result<Payment, AppError> {
+    ensure_writable()?;
+    if !amount.is_finite() || amount <= 0.0 {
+        return Err(AppError::Validation("Payment amount must be a positive number".to_string()));
+    }
+
+    let mut conn = checkout(&pool)?;
+    let tx = conn.transaction().map_err(|e| AppError::db("Failed to start transaction", e))?;
+    if !order_exists(&tx, &order_no)? {
+        return Err(AppError::NotFound(format!("Order {} not found", order_no)));
+    }
+
+    let paid_on = paid_on.map(|d| d.trim().to_string()).filter(|d| !d.is_empty());
+    tx.execute(
+        "INSERT INTO payments (order_no, amount, paid_on, method)
+         VALUES (?1, ?2, COALESCE(?3, date('now', 'localtime')), ?4)",
+        rusqlite::params![order_no, amount, paid_on, method.unwrap_or_default()],
+    )
+    .map_err(|e| AppError::db("Failed to add payment", e))?;
+
+    let id = tx.last_insert_rowid();
+    refresh_amount_paid(&tx, &order_no)?;
+    record_audit(&tx, &order_no, AUDIT_PAYMENT_ADDED, &format!("payment {}: {:.2}", id, amount))?;
+
+    let payment = tx.query_row(
+        "SELECT id, order_no, amount, paid_on, method FROM payments WHERE id = ?1",
+        [id],
+        payment_from_row,
+    )
+    .map_err(|e| AppError::db("Failed to load payment", e))?;
+
+    tx.commit().map_err(|e| AppError::db("Failed to commit transaction", e))?;
+    Ok(payment)
+}
+
+#[tauri::command]
+fn list_payments(order_no: String, pool: tauri::State<DbPool>) -> Result<Vec<Payment>, AppError> {
+    let conn = checkout(&pool)?;
+
+    let mut stmt = conn
+        .prepare("SELECT id, order_no, amount, paid_on, method FROM payments WHERE order_no = ?1 ORDER BY paid_on, id")
+        .map_err(|e| AppError::db("Failed to prepare payments query", e))?;
+
+    let payment_iter = stmt
+        .query_map([&order_no], payment_from_row)
+        .map_err(|e| AppError::db("Failed to query payments", e))?;
+
+    let mut payments = Vec::new();
+    for payment_result in payment_iter {
+        payments.push(payment_result.map_err(|e| AppError::db("Failed to parse payment", e))?);
+    }
+
+    Ok(payments)
+}
+
+// For payments entered by mistake; the order's balance is recomputed without it
+#[tauri::command]
+fn remove_payment(id: i64, pool: tauri::State<DbPool>) -> Result<(), AppError> {
+    ensure_writable()?;
+    use rusqlite::OptionalExtension;
+
+    let mut conn = checkout(&pool)?;
+    let tx = conn.transaction().map_err(|e| AppError::db("Failed to start transaction", e))?;
+
+    let (order_no, amount): (String, f64) = tx
+        .query_row("SELECT order_no, amount FROM payments WHERE id = ?1", [id], |row| Ok((row.get(0)?, row.get(1)?)))
+        .optional()
+        .map_err(|e| AppError::db("Failed to look up payment", e))?
+        .ok_or_else(|| AppError::NotFound(format!("Payment {} not found", id)))?;
+
+    tx.execute("DELETE FROM payments WHERE id = ?1", [id])
+        .map_err(|e| AppError::db("Failed to remove payment", e))?;
+    refresh_amount_paid(&tx, &order_no)?;
+    record_audit(&tx, &order_no, AUDIT_PAYMENT_REMOVED, &format!("payment {}: {:.2}", id, amount))?;
+
+    tx.commit().map_err(|e| AppError::db("Failed to commit transaction", e))?;
+    Ok(())
+}
+
+// Largest orders by total, optionally within an inclusive YYYY-MM-DD date range.
+// Quotations don't count, and items are omitted as in recent_orders.
+#[tauri::command]
+fn top_orders(limit: u32, from: Option<String>, to: Option<String>, pool: tauri::State<DbPool>) -> Result<Vec<Order>, AppError> {
+    let limit = limit.clamp(1, MAX_RECENT_ORDERS);
+    let conn = checkout(&pool)?;
+
+    query_orders(
+        &conn,
+        &format!(
+            "SELECT {} FROM orders
+             WHERE doc_type = 'order'
+               AND (?1 IS NULL OR date >= ?1)
+               AND (?2 IS NULL OR date <= ?2)
+             ORDER BY total DESC, date DESC
+             LIMIT ?3",
+            ORDER_COLUMNS
+        ),
+        rusqlite::params![from, to, limit],
+        false,
+    )
+}
+
+const DEFAULT_TALLY_SALES_LEDGER: &str = "Sales";
+const DEFAULT_TALLY_GST_LEDGER: &str = "Output GST";
+
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn tally_ledger_entry(xml: &mut String, ledger: &str, is_debit: bool, amount: f64) {
+    // Tally's sign convention: debits are negative, credits positive
+    let signed = if is_debit { -amount } else { amount };
+    xml.push_str("      <ALLLEDGERENTRIES.LIST>\n");
+    xml.push_str(&format!("       <LEDGERNAME>{}</LEDGERNAME>\n", xml_escape(ledger)));
+    xml.push_str(&format!("       <ISDEEMEDPOSITIVE>{}</ISDEEMEDPOSITIVE>\n", if is_debit { "Yes" } else { "No" }));
+    xml.push_str(&format!("       <AMOUNT>{:.2}</AMOUNT>\n", signed));
+    xml.push_str("      </ALLLEDGERENTRIES.LIST>\n");
+}
+
+// One Sales voucher per order dated within from..=to, in Tally's XML import envelope:
+// the customer's ledger is debited the total, and the sales and GST ledgers credited the
+// subtotal and GST. The party ledger is the customer name as typed, so it has to match
+// a ledger in Tally; the sales/GST ledger names come from the tally_sales_ledger and
+// tally_gst_ledger settings. GST is posted to a single ledger, not split into CGST/SGST.
+// Quotations and rejected orders are skipped. Returns the number of vouchers written.
+#[tauri::command]
+fn export_tally_xml(from: String, to: String, file_path: String, pool: tauri::State<DbPool>) -> Result<u32, AppError> {
+    let (start, end) = parse_date_range(&from, &to)?;
+    let (from, to) = (start.to_string(), end.to_string());
+
+    let conn = checkout(&pool)?;
+    let sales_ledger = read_setting(&conn, SETTING_TALLY_SALES_LEDGER)?
+        .unwrap_or_else(|| DEFAULT_TALLY_SALES_LEDGER.to_string());
+    let gst_ledger = read_setting(&conn, SETTING_TALLY_GST_LEDGER)?
+        .unwrap_or_else(|| DEFAULT_TALLY_GST_LEDGER.to_string());
+
+    let orders = query_orders(
+        &conn,
+        &format!(
+            "SELECT {} FROM orders
+             WHERE date >= ?1 AND date <= ?2 AND doc_type = 'order' AND status != 'Rejected'
+             ORDER BY date, order_no",
+            ORDER_COLUMNS
+        ),
+        [&from, &to],
+        false,
+    )?;
+
+    let mut xml = String::new();
+    xml.push_str("<ENVELOPE>\n");
+    xml.push_str(" <HEADER>\n  <TALLYREQUEST>Import Data</TALLYREQUEST>\n </HEADER>\n");
+    xml.push_str(" <BODY>\n  <IMPORTDATA>\n");
+    xml.push_str("   <REQUESTDESC>\n    <REPORTNAME>Vouchers</REPORTNAME>\n   </REQUESTDESC>\n");
+    xml.push_str("   <REQUESTDATA>\n");
+
+    for order in &orders {
+        let party = order.customer_name.trim();
+        let mut narration = format!("Order {}", order.order_no);
+        if !order.buyer_order_no.is_empty() {
+            narration.push_str(&format!(", buyer order {}", order.buyer_order_no));
+        }
+
+        xml.push_str("    <TALLYMESSAGE xmlns:UDF=\"TallyUDF\">\n");
+        xml.push_str("     <VOUCHER VCHTYPE=\"Sales\" ACTION=\"Create\">\n");
+        xml.push_str(&format!("      <DATE>{}</DATE>\n", order.date.replace('-', "")));
+        xml.push_str("      <VOUCHERTYPENAME>Sales</VOUCHERTYPENAME>\n");
+        xml.push_str(&format!("      <VOUCHERNUMBER>{}</VOUCHERNUMBER>\n", xml_escape(&order.order_no)));
+        xml.push_str(&format!("      <PARTYLEDGERNAME>{}</PARTYLEDGERNAME>\n", xml_escape(party)));
+        if !order.gstin.is_empty() {
+            xml.push_str(&format!("      <PARTYGSTIN>{}</PARTYGSTIN>\n", xml_escape(&order.gstin)));
+        }
+        xml.push_str(&format!("      <NARRATION>{}</NARRATION>\n", xml_escape(&narration)));
+        xml.push_str("      <PERSISTEDVIEW>Accounting Voucher View</PERSISTEDVIEW>\n");
+        // Tally rejects a voucher whose entries don't net to zero, so the debit is built
+        // from the two credits as written (to paise) rather than from the stored total
+        let taxable = ((order.subtotal - order.discount_amount) * 100.0).round() / 100.0;
+        let gst = (order.gst * 100.0).round() / 100.0;
+        tally_ledger_entry(&mut xml, party, true, taxable + gst);
+        tally_ledger_entry(&mut xml, &sales_ledger, false, taxable);
+        if gst > 0.0 {
+            tally_ledger_entry(&mut xml, &gst_ledger, false, gst);
+        }
+        xml.push_str("     </VOUCHER>\n");
+        xml.push_str("    </TALLYMESSAGE>\n");
+    }
+
+    xml.push_str("   </REQUESTDATA>\n");
+    xml.push_str("  </IMPORTDATA>\n </BODY>\n</ENVELOPE>\n");
+
+    std::fs::write(&file_path, xml)
+        .map_err(|e| AppError::Internal(format!("Failed to write Tally XML: {}", e)))?;
+
+    Ok(orders.len() as u32)
+}
+
+// Orders saved or updated today, most recent first. updated_at is stored in UTC, so both
+// sides are converted with SQLite's 'localtime' (the shop PC's clock, i.e. IST) before
+// comparing dates; comparing the raw UTC date would miss edits made before 05:30.
+#[tauri::command]
+fn orders_modified_today(pool: tauri::State<DbPool>) -> Result<Vec<Order>, AppError> {
+    let conn = checkout(&pool)?;
+
+    query_orders(
+        &conn,
+        &format!(
+            "SELECT {} FROM orders
+             WHERE date(updated_at, 'localtime') = date('now', 'localtime')
+             ORDER BY updated_at DESC",
+            ORDER_COLUMNS
+        ),
+        [],
+        true,
+    )
+}
+
+#[derive(Debug, Serialize)]
+struct GstBucket {
+    rate: f64,
+    #[serde(rename = "orderCount")]
+    order_count: u32,
+    #[serde(rename = "taxableValue")]
+    taxable_value: f64,
+    #[serde(rename = "taxAmount")]
+    tax_amount: f64,
+}
+
+// Taxable value and GST per rate for orders dated within from..=to, ready to copy into
+// the rate-wise tables of a GST return. Quotations and rejected orders were never
+// invoiced, so they're excluded.
+#[tauri::command]
+fn gst_summary(from: String, to: String, pool: tauri::State<DbPool>) -> Result<Vec<GstBucket>, AppError> {
+    let (start, end) = parse_date_range(&from, &to)?;
+    let (from, to) = (start.to_string(), end.to_string());
+
+    let conn = checkout(&pool)?;
+    gst_buckets(&conn, &from, &to)
+}
+
+fn gst_buckets(conn: &Connection, from: &str, to: &str) -> Result<Vec<GstBucket>, AppError> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT ROUND(gst_rate, 2) AS rate, COUNT(*), COALESCE(SUM(subtotal - discount_amount), 0), COALESCE(SUM(gst), 0)
+             FROM orders
+             WHERE date >= ?1 AND date <= ?2 AND doc_type = 'order' AND status != 'Rejected'
+             GROUP BY rate
+             ORDER BY rate",
+        )
+        .map_err(|e| AppError::db("Failed to prepare query", e))?;
+
+    let bucket_iter = stmt
+        .query_map([from, to], |row| {
+            Ok(GstBucket {
+                rate: row.get(0)?,
+                order_count: row.get(1)?,
+                taxable_value: row.get(2)?,
+                tax_amount: row.get(3)?,
+            })
+        })
+        .map_err(|e| AppError::db("Failed to query GST summary", e))?;
+
+    let mut buckets = Vec::new();
+    for bucket_result in bucket_iter {
+        buckets.push(bucket_result.map_err(|e| AppError::db("Failed to parse GST bucket", e))?);
+    }
+    Ok(buckets)
+}
+
+#[derive(Debug, Serialize)]
+struct GstLiability {
+    #[serde(rename = "totalGst")]
+    total_gst: f64,
+    #[serde(rename = "byRate")]
+    by_rate: Vec<GstBucket>,
+}
+
+// GST owed to the government for orders dated within from..=to. Liability arises when the
+// invoice is raised, not when the customer pays, so unpaid orders count too; the buckets
+// are the same ones gst_summary returns and total_gst is their sum.
+#[tauri::command]
+fn gst_liability(from: String, to: String, pool: tauri::State<DbPool>) -> Result<GstLiability, AppError> {
+    let (start, end) = parse_date_range(&from, &to)?;
+    let (from, to) = (start.to_string(), end.to_string());
+
+    let conn = checkout(&pool)?;
+    let by_rate = gst_buckets(&conn, &from, &to)?;
+    let total_gst = round2(by_rate.iter().map(|b| b.tax_amount).sum());
+    Ok(GstLiability { total_gst, by_rate })
+}
+
+// Closes gaps left by deleted lines (1, 2, 4, 5 -> 1, 2, 3, 4), keeping the current order;
+// rows sharing a number keep their insertion order. Counts as an edit of the order, so
+// updated_at and version move too.
+#[tauri::command]
+fn renumber_items(order_no: String, pool: tauri::State<DbPool>) -> Result<(), AppError> {
+    ensure_writable()?;
+    let mut conn = checkout(&pool)?;
+    let tx = conn.transaction().map_err(|e| AppError::db("Failed to start transaction", e))?;
+    if !order_exists(&tx, &order_no)? {
+        return Err(AppError::NotFound(format!("Order {} not found", order_no)));
+    }
+    ensure_order_unlocked(&tx, &order_no)?;
+
+    let ids = {
+        let mut stmt = tx
+            .prepare("SELECT id FROM order_items WHERE order_no = ?1 ORDER BY sl_no, id")
+            .map_err(|e| AppError::db("Failed to prepare items query", e))?;
+        let id_iter = stmt
+            .query_map([&order_no], |row| row.get::<_, i64>(0))
+            .map_err(|e| AppError::db("Failed to query items", e))?;
+        id_iter
+            .collect::<SqlResult<Vec<_>>>()
+            .map_err(|e| AppError::db("Failed to parse item", e))?
+    };
+
+    for (index, id) in ids.iter().enumerate() {
+        tx.execute(
+            "UPDATE order_items SET sl_no = ?1 WHERE id = ?2",
+            rusqlite::params![index as u32 + 1, id],
+        ).map_err(|e| AppError::db("Failed to renumber item", e))?;
+    }
+
+    tx.execute(
+        "UPDATE orders SET printed_at = NULL, updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now'), version = version + 1 WHERE order_no = ?1",
+        [&order_no],
+    ).map_err(|e| AppError::db("Failed to update order", e))?;
+    stamp_invoice_hash(&tx, &order_no)?;
+    record_audit(&tx, &order_no, AUDIT_RENUMBERED, "")?;
+
+    tx.commit().map_err(|e| AppError::db("Failed to commit transaction", e))?;
+    Ok(())
+}
+
+#[derive(Debug, Serialize)]
+struct OrderTotals {
+    subtotal: f64,
+    gst: f64,
+    total: f64,
+    #[serde(rename = "storedSubtotal")]
+    stored_subtotal: f64,
+    #[serde(rename = "storedGst")]
+    stored_gst: f64,
+    #[serde(rename = "storedTotal")]
+    stored_total: f64,
+    // False when any stored total or item amount is more than a paisa off the fresh one
+    matches: bool,
+    persisted: bool,
+}
+
+// Totals recomputed from the order's items and gst_rate, alongside what's stored. Nothing
+// is written unless `persist` is set, in which case the corrected item amounts and totals
+// replace the stored ones.
+#[tauri::command]
+fn recompute_order_totals(order_no: String, persist: Option<bool>, pool: tauri::State<DbPool>) -> Result<OrderTotals, AppError> {
+    let persist = persist.unwrap_or(false);
+    if persist {
+        ensure_writable()?;
+    }
+
+    let mut conn = checkout(&pool)?;
+    let mode = get_rounding_mode_setting(&conn)?;
+    let tx = conn.transaction().map_err(|e| AppError::db("Failed to start transaction", e))?;
+
+    let stored = load_order_by_no(&tx, &order_no)?
+        .ok_or_else(|| AppError::NotFound(format!("Order {} not found", order_no)))?;
+    let mut fresh = stored.clone();
+    recalculate_order(&mut fresh, mode);
+
+    // Item errors can cancel out in the subtotal, so each amount is compared as well
+    let matches = [
+        (fresh.subtotal, stored.subtotal),
+        (fresh.gst, stored.gst),
+        (fresh.total, stored.total),
+    ]
+    .into_iter()
+    .chain(fresh.items.iter().zip(&stored.items).map(|(a, b)| (a.amount, b.amount)))
+    .all(|(a, b)| (a - b).abs() <= AMOUNT_TOLERANCE);
+
+    let persisted = persist && !matches;
+    if persisted {
+        ensure_order_unlocked(&tx, &order_no)?;
+        write_recalculated_order(&tx, &fresh)?;
+        record_audit(&tx, &order_no, AUDIT_RECALCULATED, "recompute_order_totals")?;
+        tx.commit().map_err(|e| AppError::db("Failed to commit transaction", e))?;
+    }
+
+    Ok(OrderTotals {
+        subtotal: fresh.subtotal,
+        gst: fresh.gst,
+        total: fresh.total,
+        stored_subtotal: stored.subtotal,
+        stored_gst: stored.gst,
+        stored_total: stored.total,
+        matches,
+        persisted,
+    })
+}
+
+// Text invoice for one order, laid out with render_text_pdf. It carries the same
+// information as the printed invoice from the order screen, one line per item.
+fn render_invoice_pdf(order: &Order, company: &CompanyProfile) -> Result<Vec<u8>, AppError> {
+    let mut lines = vec![
+        company.tagline.clone(),
+        company.address.clone(),
+        format!("E-mail: {} | Ph: {}", company.email, company.phone),
+    ];
+    if !company.gstin.is_empty() {
+        lines.push(format!("GSTIN: {}", company.gstin));
+    }
+    lines.push(String::new());
+    lines.push(format!("Order No: {}    Date: {}", order.order_no, order.date));
+    lines.push(format!("Customer: {}", order.customer_name));
+    if !order.contact_person.is_empty() || !order.phone.is_empty() {
+        lines.push(format!("Contact: {}  {}", order.contact_person, order.phone));
+    }
+    if !order.gstin.is_empty() {
+        lines.push(format!("Customer GSTIN: {}", order.gstin));
+    }
+    if !order.buyer_order_no.is_empty() {
+        lines.push(format!("Buyer's Order: {} dated {}", order.buyer_order_no, order.buyer_order_date));
+    }
+    lines.push(String::new());
+    lines.push("Items".to_string());
+    for item in &order.items {
+        let mut description = item.item_type.clone();
+        for (label, value) in [("HSN", &item.hsn), ("L", &item.length), ("Dia", &item.dia), ("Shore", &item.shore)] {
+            if !value.is_empty() {
+                description.push_str(&format!(", {} {}", label, value));
+            }
+        }
+        lines.push(format!(
+            "{}. {} - {} x {} = {}",
+            item.sl_no,
+            description,
+            item.qty,
+            format_rupees(item.rate),
+            format_rupees(item.amount)
+        ));
+    }
+    lines.push(String::new());
+    lines.push(format!("Subtotal: {}", format_rupees(order.subtotal)));
+    if order.discount_amount > 0.0 {
+        lines.push(format!("Discount: {}", format_rupees(order.discount_amount)));
+    }
+    lines.push(format!("GST ({}%): {}", order.gst_rate, format_rupees(order.gst)));
+    lines.push(format!("Total: {}", format_rupees(order.total)));
+    lines.push(format!("Amount in words: {}", amount_to_words(order.total)?));
+
+    Ok(render_text_pdf(&format!("{} - Order {}", company.name, order.order_no), &lines))
+}
+
+// Order numbers can contain characters that aren't allowed in file names (ORD/24/001)
+fn invoice_file_name(order_no: &str) -> String {
+    let stem: String = order_no
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    format!("{}.pdf", stem)
+}
+
+// Every order dated within from..=to as its own PDF, bundled into one zip for audits.
+// Quotations are skipped. Returns the number of invoices written.
+#[tauri::command]
+fn export_invoices_zip(from: String, to: String, file_path: String, pool: tauri::State<DbPool>) -> Result<u32, AppError> {
+    use std::io::Write;
+
+    let (start, end) = parse_date_range(&from, &to)?;
+    let (from, to) = (start.to_string(), end.to_string());
+
+    let conn = checkout(&pool)?;
+    let company = load_company_profile(&conn)?;
+    let orders = query_orders(
+        &conn,
+        &format!(
+            "SELECT {} FROM orders WHERE date >= ?1 AND date <= ?2 AND doc_type = 'order' ORDER BY date, order_no",
+            ORDER_COLUMNS
+        ),
+        [&from, &to],
+        true,
+    )?;
+
+    let file = std::fs::File::create(&file_path)
+        .map_err(|e| AppError::Internal(format!("Failed to create zip file: {}", e)))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+
+    let mut used_names = std::collections::HashSet::new();
+    for order in &orders {
+        // Two order numbers can sanitize to the same name; number the later ones
+        let mut name = invoice_file_name(&order.order_no);
+        let mut suffix = 2;
+        while !used_names.insert(name.clone()) {
+            name = invoice_file_name(&format!("{}-{}", order.order_no, suffix));
+            suffix += 1;
+        }
+
+        let pdf = render_invoice_pdf(order, &company)?;
+        zip.start_file(name, options)
+            .map_err(|e| AppError::Internal(format!("Failed to add invoice to zip: {}", e)))?;
+        zip.write_all(&pdf)
+            .map_err(|e| AppError::Internal(format!("Failed to write invoice to zip: {}", e)))?;
+    }
+
+    zip.finish()
+        .map_err(|e| AppError::Internal(format!("Failed to finish zip file: {}", e)))?;
+
+    Ok(orders.len() as u32)
+}
+
+#[derive(Debug, Serialize)]
+struct AppInfo {
+    #[serde(rename = "appVersion")]
+    app_version: String,
+    #[serde(rename = "schemaVersion")]
+    schema_version: u32,
+    // The newest migration this build knows about; a database behind it failed to migrate
+    #[serde(rename = "expectedSchemaVersion")]
+    expected_schema_version: u32,
+    #[serde(rename = "dbPath")]
+    db_path: String,
+    #[serde(rename = "readOnly")]
+    read_only: bool,
+}
+
+// Build and database details for support requests
+#[tauri::command]
+fn get_app_info(pool: tauri::State<DbPool>) -> Result<AppInfo, AppError> {
+    let conn = checkout(&pool)?;
+    let schema_version = current_schema_version(&conn)
+        .map_err(|e| AppError::db("Failed to read schema version", e))?;
+
+    Ok(AppInfo {
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        schema_version,
+        expected_schema_version: MIGRATIONS.iter().map(|m| m.version).max().unwrap_or(0),
+        db_path: get_db_path().display().to_string(),
+        read_only: read_only_mode(),
+    })
+}
+
+#[derive(Debug, Serialize)]
+struct DuplicateGroup {
+    #[serde(rename = "customerName")]
+    customer_name: String,
+    date: String,
+    total: f64,
+    orders: Vec<OrderSummary>,
+}
+
+// What makes two orders "the same" beyond the header: their lines, ignoring sl_no and
+// the order they were entered in
+fn item_signature(items: &[OrderItem]) -> Vec<String> {
+    let mut signature: Vec<String> = items
+        .iter()
+        .map(|item| {
+            format!(
+                "{}|{}|{}|{}|{}|{:.2}|{:.2}",
+                item.item_type.trim().to_lowercase(),
+                item.length.trim(),
+                item.dia.trim().to_lowercase(),
+                item.shore.trim().to_lowercase(),
+                item.qty,
+                item.rate,
+                item.amount
+            )
+        })
+        .collect();
+    signature.sort();
+    signature
+}
+
+// Candidate duplicates for a person to review: orders of the same type with the same
+// customer (trimmed, case-insensitive), date and total, whose item lists also match.
+// Nothing is merged or deleted here.
+#[tauri::command]
+fn find_duplicate_orders(pool: tauri::State<DbPool>) -> Result<Vec<DuplicateGroup>, AppError> {
+    let conn = checkout(&pool)?;
+
+    // Narrow down in SQL first so only orders that share a header are loaded with items
+    let orders = query_orders(
+        &conn,
+        &format!(
+            "SELECT {} FROM orders o
+             WHERE EXISTS (
+                 SELECT 1 FROM orders d
+                 WHERE d.order_no != o.order_no
+                   AND d.doc_type = o.doc_type
+                   AND TRIM(d.customer_name) = TRIM(o.customer_name) COLLATE NOCASE
+                   AND d.date = o.date
+                   AND ROUND(d.total, 2) = ROUND(o.total, 2)
+             )
+             ORDER BY date DESC, customer_name, order_no",
+            ORDER_COLUMNS
+        ),
+        [],
+        true,
+    )?;
+
+    let mut groups: Vec<(String, Vec<String>, Vec<Order>)> = Vec::new();
+    for order in orders {
+        let header = format!(
+            "{}|{}|{}|{:.2}",
+            order.doc_type,
+            order.customer_name.trim().to_lowercase(),
+            order.date,
+            order.total
+        );
+        let signature = item_signature(&order.items);
+        match groups.iter_mut().find(|(h, s, _)| *h == header && *s == signature) {
+            Some((_, _, members)) => members.push(order),
+            None => groups.push((header, signature, vec![order])),
+        }
+    }
+
+    Ok(groups
+        .into_iter()
+        .filter(|(_, _, members)| members.len() > 1)
+        .map(|(_, _, members)| DuplicateGroup {
+            customer_name: members[0].customer_name.trim().to_string(),
+            date: members[0].date.clone(),
+            total: members[0].total,
+            orders: members.into_iter().map(OrderSummary::from).collect(),
+        })
+        .collect())
+}
+
+// Flat list of every line item with its order's details, one row per item, for the
+// production planner's capacity sheet. Quotations aren't work yet, so they're left out;
+// the status column lets completed or rejected orders be filtered there instead.
+// Returns the number of item rows written.
+#[tauri::command]
+fn export_all_items(file_path: String, pool: tauri::State<DbPool>) -> Result<u32, AppError> {
+    let conn = checkout(&pool)?;
+    let columns = prefixed_item_columns("i");
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {}, o.order_no, o.customer_name, o.date, o.status, o.machine_name
+             FROM order_items i
+             JOIN orders o ON o.order_no = i.order_no
+             WHERE o.doc_type = 'order'
+             ORDER BY o.date, o.order_no, i.sl_no",
+            columns
+        ))
+        .map_err(|e| AppError::db("Failed to prepare items query", e))?;
+
+    let row_iter = stmt
+        .query_map([], |row| {
+            let item = item_from_row(row)?;
+            let order_info: [String; 5] = [
+                row.get(10)?,
+                row.get(11)?,
+                row.get(12)?,
+                row.get(13)?,
+                row.get::<_, Option<String>>(14)?.unwrap_or_default(),
+            ];
+            Ok((item, order_info))
+        })
+        .map_err(|e| AppError::db("Failed to query items", e))?;
+
+    let mut workbook = rust_xlsxwriter::Workbook::new();
+    let worksheet = workbook.add_worksheet();
+    let header_format = excel_header_format();
+    let currency_format = excel_currency_format();
+
+    let headers = [
+        "Order No", "Customer Name", "Date", "Status", "Machine Name", "Sl No", "Item Type", "HSN",
+        "Qty", "Length", "Dia", "Shore", "Remarks", "Rate", "Amount",
+    ];
+    for (col, header) in headers.iter().enumerate() {
+        worksheet.write_string_with_format(0, col as u16, *header, &header_format)
+            .map_err(|e| AppError::Internal(format!("Failed to write header: {}", e)))?;
+    }
+
+    let mut count = 0u32;
+    for row_result in row_iter {
+        let (item, order_info) = row_result.map_err(|e| AppError::db("Failed to parse item", e))?;
+        let row_num = count + 1;
+        for (col, value) in order_info.iter().enumerate() {
+            worksheet.write_string(row_num, col as u16, value)
+                .map_err(|e| AppError::Internal(format!("Failed to write order details: {}", e)))?;
+        }
+        worksheet.write_number(row_num, 5, item.sl_no)
+            .map_err(|e| AppError::Internal(format!("Failed to write slNo: {}", e)))?;
+        for (col, value) in [(6, &item.item_type), (7, &item.hsn)] {
+            worksheet.write_string(row_num, col, value)
+                .map_err(|e| AppError::Internal(format!("Failed to write item: {}", e)))?;
+        }
+        worksheet.write_number(row_num, 8, item.qty)
+            .map_err(|e| AppError::Internal(format!("Failed to write qty: {}", e)))?;
+        for (col, value) in [(9, &item.length), (10, &item.dia), (11, &item.shore), (12, &item.remarks)] {
+            worksheet.write_string(row_num, col, value)
+                .map_err(|e| AppError::Internal(format!("Failed to write item: {}", e)))?;
+        }
+        worksheet.write_number_with_format(row_num, 13, item.rate, &currency_format)
+            .map_err(|e| AppError::Internal(format!("Failed to write rate: {}", e)))?;
+        worksheet.write_number_with_format(row_num, 14, item.amount, &currency_format)
+            .map_err(|e| AppError::Internal(format!("Failed to write amount: {}", e)))?;
+        count += 1;
+    }
+
+    worksheet.autofit();
+    workbook.save(&file_path)
+        .map_err(|e| AppError::Internal(format!("Failed to save Excel file: {}", e)))?;
+
+    Ok(count)
+}
+
+// Moves orders to another machine (e.g. when one breaks down), all or nothing. Order
+// numbers that don't exist, are locked or are already on that machine are skipped, so
+// the count returned is what actually changed.
+#[tauri::command]
+fn reassign_machine(order_nos: Vec<String>, machine_name: String, pool: tauri::State<DbPool>) -> Result<u32, AppError> {
+    ensure_writable()?;
+    let machine_name = machine_name.trim();
+    if machine_name.is_empty() {
+        return Err(AppError::Validation("Machine name is required".to_string()));
+    }
+
+    let mut conn = checkout(&pool)?;
+    let tx = conn.transaction().map_err(|e| AppError::db("Failed to start transaction", e))?;
+
+    let mut changed = 0u32;
+    for order_no in &order_nos {
+        let updated = tx.execute(
+            "UPDATE orders SET machine_name = ?1, printed_at = NULL, updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now'), version = version + 1
+             WHERE order_no = ?2 AND machine_name IS NOT ?1 AND locked = 0",
+            [machine_name, order_no.as_str()],
+        ).map_err(|e| AppError::db("Failed to reassign machine", e))?;
+        if updated > 0 {
+            record_audit(&tx, order_no, AUDIT_MACHINE_CHANGED, &format!("to {}", machine_name))?;
+            changed += 1;
+        }
+    }
+
+    tx.commit().map_err(|e| AppError::db("Failed to commit transaction", e))?;
+    Ok(changed)
+}
+
+// Machine names as actually used on orders, for the machine field's suggestions.
+// Trimmed and de-duplicated case-insensitively so "Press 1 " and "press 1" show once.
+#[tauri::command]
+fn list_machines(pool: tauri::State<DbPool>) -> Result<Vec<String>, AppError> {
+    let conn = checkout(&pool)?;
+    let mut stmt = conn.prepare(
+        "SELECT MIN(TRIM(machine_name)) FROM orders
+         WHERE TRIM(COALESCE(machine_name, '')) != ''
+         GROUP BY TRIM(machine_name) COLLATE NOCASE
+         ORDER BY 1 COLLATE NOCASE",
+    ).map_err(|e| AppError::db("Failed to prepare query", e))?;
+
+    let machines = stmt.query_map([], |row| row.get::<_, String>(0))
+        .map_err(|e| AppError::db("Failed to query machines", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| AppError::db("Failed to read machine name", e))?;
+    Ok(machines)
+}
+
+// Writes every order (with items) as one JSON object per line. Rows are streamed
+// straight from the query to a buffered file, so only one order is held in memory at
+// a time. Returns the number of lines written.
+#[tauri::command]
+fn export_orders_ndjson(file_path: String, pool: tauri::State<DbPool>) -> Result<u32, AppError> {
+    use std::io::Write;
+
+    let conn = checkout(&pool)?;
+    let file = std::fs::File::create(&file_path)
+        .map_err(|e| AppError::Internal(format!("Failed to create NDJSON file: {}", e)))?;
+    let mut writer = std::io::BufWriter::new(file);
+
+    let mut stmt = conn
+        .prepare(&format!("SELECT {} FROM orders ORDER BY created_date, order_no", ORDER_COLUMNS))
+        .map_err(|e| AppError::db("Failed to prepare query", e))?;
+    let order_iter = stmt
+        .query_map([], order_from_row)
+        .map_err(|e| AppError::db("Failed to query orders", e))?;
+
+    let mut count = 0u32;
+    for order_result in order_iter {
+        let mut order = order_result.map_err(|e| AppError::db("Failed to parse order", e))?;
+        order.items = load_items_for_order(&conn, &order.order_no)?;
+
+        serde_json::to_writer(&mut writer, &order)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize order {}: {}", order.order_no, e)))?;
+        writer.write_all(b"\n")
+            .map_err(|e| AppError::Internal(format!("Failed to write NDJSON file: {}", e)))?;
+        count += 1;
+    }
+
+    writer.flush()
+        .map_err(|e| AppError::Internal(format!("Failed to write NDJSON file: {}", e)))?;
+    Ok(count)
+}
+
+// Orders saved without any line items (they show zero totals), oldest first so the
+// backlog of incomplete entries can be worked through. The anti-join runs in a
+// subquery because order_items shares column names (order_no, remarks) with orders.
+#[tauri::command]
+fn orders_without_items(pool: tauri::State<DbPool>) -> Result<Vec<Order>, AppError> {
+    let conn = checkout(&pool)?;
+
+    query_orders(
+        &conn,
+        &format!(
+            "SELECT {} FROM orders
+             WHERE order_no IN (
+                 SELECT o.order_no FROM orders o
+                 LEFT JOIN order_items i ON i.order_no = o.order_no
+                 WHERE i.id IS NULL
+             )
+             ORDER BY created_date",
+            ORDER_COLUMNS
+        ),
+        [],
+        false,
+    )
+}
+
+// Statuses whose prices can still change: work not started or under way
+const PENDING_STATUSES: [&str; 2] = ["New", "In Progress"];
+
+// Reprices every line of an item type (e.g. when a rubber grade's price changes), then
+// recomputes those lines' amounts and their orders' totals, all in one transaction.
+// Locked orders are never repriced, and with only_pending neither are delivered,
+// rejected or ready orders. Returns the number of items whose rate changed.
+#[tauri::command]
+fn update_rates_for_type(item_type: String, new_rate: f64, only_pending: bool, pool: tauri::State<DbPool>) -> Result<u32, AppError> {
+    ensure_writable()?;
+    let item_type = item_type.trim();
+    if item_type.is_empty() {
+        return Err(AppError::Validation("Item type is required".to_string()));
+    }
+    if !new_rate.is_finite() || new_rate < 0.0 {
+        return Err(AppError::Validation(format!("Invalid rate: {}", new_rate)));
+    }
+
+    let mut conn = checkout(&pool)?;
+    let mode = get_rounding_mode_setting(&conn)?;
+    let tx = conn.transaction().map_err(|e| AppError::db("Failed to start transaction", e))?;
+
+    let order_nos = {
+        let mut stmt = tx
+            .prepare(
+                "SELECT DISTINCT i.order_no FROM order_items i
+                 JOIN orders o ON o.order_no = i.order_no
+                 WHERE TRIM(i.item_type) = ?1 COLLATE NOCASE
+                   AND i.rate != ?2
+                   AND o.locked = 0
+                   AND (?3 = 0 OR o.status IN (?4, ?5))",
+            )
+            .map_err(|e| AppError::db("Failed to prepare query", e))?;
+        let rows = stmt
+            .query_map(
+                rusqlite::params![item_type, new_rate, only_pending, PENDING_STATUSES[0], PENDING_STATUSES[1]],
+                |row| row.get::<_, String>(0),
+            )
+            .map_err(|e| AppError::db("Failed to query items", e))?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::db("Failed to parse order number", e))?
+    };
+
+    let mut changed = 0u32;
+    for order_no in &order_nos {
+        changed += tx.execute(
+            "UPDATE order_items SET rate = ?1
+             WHERE order_no = ?2 AND TRIM(item_type) = ?3 COLLATE NOCASE AND rate != ?1",
+            rusqlite::params![new_rate, order_no, item_type],
+        ).map_err(|e| AppError::db("Failed to update item rate", e))? as u32;
+
+        let mut order = load_order_by_no(&tx, order_no)?
+            .ok_or_else(|| AppError::NotFound(format!("Order {} not found", order_no)))?;
+        recalculate_order(&mut order, mode);
+        write_recalculated_order(&tx, &order)?;
+        record_audit(&tx, order_no, AUDIT_REPRICED, &format!("{} at {:.2}", item_type, new_rate))?;
+    }
+
+    tx.commit().map_err(|e| AppError::db("Failed to commit transaction", e))?;
+    Ok(changed)
+}
+
+// Renders the delivery challan for an order as a standalone HTML page, so the layout
+// is the same whichever screen prints it. A challan goes with the goods, so it lists
+// quantities and specs but no prices. The challan number and date are the order's
+// delivery note fields, falling back to the order number and date when those are empty.
+fn render_challan_html(order: &Order, company: &CompanyProfile) -> String {
+    let or_fallback = |value: &str, fallback: &str| {
+        if value.trim().is_empty() { fallback.to_string() } else { value.trim().to_string() }
+    };
+    let challan_no = or_fallback(&order.delivery_note, &order.order_no);
+    let challan_date = or_fallback(&order.delivery_note_date, &order.date);
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!("<title>Delivery Challan {}</title>\n", xml_escape(&challan_no)));
+    html.push_str(
+        "<style>\n\
+         body { font-family: Arial, sans-serif; font-size: 12px; margin: 24px; }\n\
+         .letterhead { text-align: center; border-bottom: 2px solid #000; padding-bottom: 8px; }\n\
+         .letterhead h1 { margin: 0; font-size: 22px; }\n\
+         .letterhead p { margin: 2px 0; }\n\
+         h2 { text-align: center; margin: 12px 0; font-size: 16px; }\n\
+         .details { display: flex; justify-content: space-between; margin-bottom: 12px; }\n\
+         .details p { margin: 2px 0; }\n\
+         table { width: 100%; border-collapse: collapse; }\n\
+         th, td { border: 1px solid #000; padding: 4px 6px; text-align: left; }\n\
+         td.num { text-align: right; }\n\
+         .signatures { display: flex; justify-content: space-between; margin-top: 60px; }\n\
+         </style>\n</head>\n<body>\n",
+    );
+
+    html.push_str("<div class=\"letterhead\">\n");
+    html.push_str(&format!("<h1>{}</h1>\n", xml_escape(&company.name)));
+    for line in [&company.tagline, &company.address] {
+        if !line.is_empty() {
+            html.push_str(&format!("<p>{}</p>\n", xml_escape(line)));
+        }
+    }
+    html.push_str(&format!("<p>E-mail: {} | Ph: {}</p>\n", xml_escape(&company.email), xml_escape(&company.phone)));
+    if !company.gstin.is_empty() {
+        html.push_str(&format!("<p>GSTIN: {}</p>\n", xml_escape(&company.gstin)));
+    }
+    html.push_str("</div>\n<h2>DELIVERY CHALLAN</h2>\n");
+
+    html.push_str("<div class=\"details\">\n<div>\n");
+    html.push_str(&format!("<p><strong>To:</strong> {}</p>\n", xml_escape(&order.customer_name)));
+    if !order.contact_person.is_empty() {
+        html.push_str(&format!("<p><strong>Contact:</strong> {}</p>\n", xml_escape(&order.contact_person)));
+    }
+    if !order.phone.is_empty() {
+        html.push_str(&format!("<p><strong>Phone:</strong> {}</p>\n", xml_escape(&order.phone)));
+    }
+    if !order.gstin.is_empty() {
+        html.push_str(&format!("<p><strong>GSTIN:</strong> {}</p>\n", xml_escape(&order.gstin)));
+    }
+    html.push_str("</div>\n<div>\n");
+    html.push_str(&format!("<p><strong>Challan No:</strong> {}</p>\n", xml_escape(&challan_no)));
+    html.push_str(&format!("<p><strong>Date:</strong> {}</p>\n", xml_escape(&challan_date)));
+    html.push_str(&format!("<p><strong>Order No:</strong> {}</p>\n", xml_escape(&order.order_no)));
+    if !order.buyer_order_no.is_empty() {
+        html.push_str(&format!(
+            "<p><strong>Buyer's Order:</strong> {} dated {}</p>\n",
+            xml_escape(&order.buyer_order_no),
+            xml_escape(&order.buyer_order_date)
+        ));
+    }
+    html.push_str("</div>\n</div>\n");
+
+    html.push_str(
+        "<table>\n<thead>\n<tr><th>Sl No</th><th>Description</th><th>HSN</th><th>Length</th>\
+         <th>Dia</th><th>Shore</th><th>Qty</th><th>Remarks</th></tr>\n</thead>\n<tbody>\n",
+    );
+    for item in &order.items {
+        html.push_str(&format!(
+            "<tr><td class=\"num\">{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td class=\"num\">{}</td><td>{}</td></tr>\n",
+            item.sl_no,
+            xml_escape(&item.item_type),
+            xml_escape(&item.hsn),
+            xml_escape(&item.length),
+            xml_escape(&item.dia),
+            xml_escape(&item.shore),
+            item.qty,
+            xml_escape(&item.remarks)
+        ));
+    }
+    html.push_str("</tbody>\n</table>\n");
+
+    if !order.remarks.is_empty() {
+        html.push_str(&format!("<p><strong>Remarks:</strong> {}</p>\n", xml_escape(&order.remarks)));
+    }
+    html.push_str(&format!(
+        "<div class=\"signatures\">\n<div>Receiver's Signature</div>\n<div>For {}</div>\n</div>\n",
+        xml_escape(&company.name)
+    ));
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+#[tauri::command]
+fn generate_challan_html(order_no: String, pool: tauri::State<DbPool>) -> Result<String, AppError> {
+    let conn = checkout(&pool)?;
+    let order = load_order_by_no(&conn, &order_no)?
+        .ok_or_else(|| AppError::NotFound(format!("Order {} not found", order_no)))?;
+    let company = load_company_profile(&conn)?;
+    Ok(render_challan_html(&order, &company))
+}
+
+#[derive(Debug, Serialize)]
+struct PeriodComparison {
+    current: SummaryReport,
+    previous: SummaryReport,
+    // Percentage changes from previous to current; None when the previous period had
+    // nothing to compare against
+    #[serde(rename = "ordersChangePct")]
+    orders_change_pct: Option<f64>,
+    #[serde(rename = "revenueChangePct")]
+    revenue_change_pct: Option<f64>,
+}
+
+fn percent_change(previous: f64, current: f64) -> Option<f64> {
+    if previous == 0.0 {
+        None
+    } else {
+        Some(((current - previous) / previous * 10000.0).round() / 100.0)
+    }
+}
+
+// Summary for [from, to] next to the same figures for the equal-length range just
+// before it (a 31-day range is compared with the 31 days preceding `from`)
+#[tauri::command]
+fn period_comparison(from: String, to: String, pool: tauri::State<DbPool>) -> Result<PeriodComparison, AppError> {
+    use chrono::Duration;
+
+    let (start, end) = parse_date_range(&from, &to)?;
+    let previous_end = start - Duration::days(1);
+    let previous_start = previous_end - (end - start);
+
+    let conn = checkout(&pool)?;
+    let current = summary_between(&conn, &start.format("%Y-%m-%d").to_string(), &end.format("%Y-%m-%d").to_string())?;
+    let previous = summary_between(
+        &conn,
+        &previous_start.format("%Y-%m-%d").to_string(),
+        &previous_end.format("%Y-%m-%d").to_string(),
+    )?;
+
+    Ok(PeriodComparison {
+        orders_change_pct: percent_change(previous.total_orders as f64, current.total_orders as f64),
+        revenue_change_pct: percent_change(previous.total_revenue, current.total_revenue),
+        current,
+        previous,
+    })
+}
+
+const MONTH_NAMES: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+// Revenue matrix for a calendar year: one row per customer (alphabetical), one column
+// per month, with a Total column and a Total row. Customers are matched ignoring case
+// and surrounding spaces; quotations are excluded.
+#[tauri::command]
+fn export_pivot(year: i32, file_path: String, pool: tauri::State<DbPool>) -> Result<(), AppError> {
+    use std::collections::BTreeMap;
+
+    let conn = checkout(&pool)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT TRIM(customer_name), CAST(substr(date, 6, 2) AS INTEGER), COALESCE(SUM(total), 0)
+             FROM orders
+             WHERE doc_type = 'order' AND substr(date, 1, 4) = ?1
+             GROUP BY LOWER(TRIM(customer_name)), substr(date, 6, 2)",
+        )
+        .map_err(|e| AppError::db("Failed to prepare pivot query", e))?;
+    let cells = stmt
+        .query_map([format!("{:04}", year)], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?, row.get::<_, f64>(2)?))
+        })
+        .map_err(|e| AppError::db("Failed to query pivot", e))?;
+
+    // Keyed on the lowercased name so spellings that differ only in case share a row,
+    // displayed with the first spelling seen
+    let mut rows: BTreeMap<String, (String, [f64; 12])> = BTreeMap::new();
+    for cell in cells {
+        let (customer, month, revenue) = cell.map_err(|e| AppError::db("Failed to parse pivot cell", e))?;
+        if !(1..=12).contains(&month) {
+            continue;
+        }
+        let entry = rows.entry(customer.to_lowercase()).or_insert_with(|| (customer, [0.0; 12]));
+        entry.1[(month - 1) as usize] += revenue;
+    }
+
+    let mut workbook = rust_xlsxwriter::Workbook::new();
+    let worksheet = workbook.add_worksheet();
+    let header_format = excel_header_format();
+    let currency_format = excel_currency_format();
+    let total_format = excel_currency_format().set_bold();
+    let total_col = (MONTH_NAMES.len() + 1) as u16;
+
+    worksheet.write_string_with_format(0, 0, "Customer", &header_format)
+        .map_err(|e| AppError::Internal(format!("Failed to write header: {}", e)))?;
+    for (i, month) in MONTH_NAMES.iter().enumerate() {
+        worksheet.write_string_with_format(0, (i + 1) as u16, format!("{} {}", month, year), &header_format)
+            .map_err(|e| AppError::Internal(format!("Failed to write header: {}", e)))?;
+    }
+    worksheet.write_string_with_format(0, total_col, "Total", &header_format)
+        .map_err(|e| AppError::Internal(format!("Failed to write header: {}", e)))?;
+
+    let mut month_totals = [0.0; 12];
+    let mut row_num = 1u32;
+    for (customer, revenue) in rows.values() {
+        worksheet.write_string(row_num, 0, customer)
+            .map_err(|e| AppError::Internal(format!("Failed to write customer: {}", e)))?;
+        for (i, amount) in revenue.iter().enumerate() {
+            month_totals[i] += amount;
+            worksheet.write_number_with_format(row_num, (i + 1) as u16, *amount, &currency_format)
+                .map_err(|e| AppError::Internal(format!("Failed to write revenue: {}", e)))?;
+        }
+        worksheet.write_number_with_format(row_num, total_col, revenue.iter().sum::<f64>(), &total_format)
+            .map_err(|e| AppError::Internal(format!("Failed to write total: {}", e)))?;
+        row_num += 1;
+    }
+
+    worksheet.write_string_with_format(row_num, 0, "Total", &header_format)
+        .map_err(|e| AppError::Internal(format!("Failed to write total: {}", e)))?;
+    for (i, amount) in month_totals.iter().enumerate() {
+        worksheet.write_number_with_format(row_num, (i + 1) as u16, *amount, &total_format)
+            .map_err(|e| AppError::Internal(format!("Failed to write total: {}", e)))?;
+    }
+    worksheet.write_number_with_format(row_num, total_col, month_totals.iter().sum::<f64>(), &total_format)
+        .map_err(|e| AppError::Internal(format!("Failed to write total: {}", e)))?;
+
+    worksheet.autofit();
+    workbook.save(&file_path)
+        .map_err(|e| AppError::Internal(format!("Failed to save Excel file: {}", e)))?;
+
+    Ok(())
+}
+
+// Marks an order as finalized (e.g. once its invoice has been sent); see
+// ensure_order_unlocked for what that blocks
+#[tauri::command]
+fn lock_order(order_no: String, pool: tauri::State<DbPool>) -> Result<(), AppError> {
+    set_order_locked(&order_no, true, &pool)
+}
+
+#[tauri::command]
+fn unlock_order(order_no: String, pool: tauri::State<DbPool>) -> Result<(), AppError> {
+    set_order_locked(&order_no, false, &pool)
+}
+
+// Bumps version as well, so a form opened before the lock changed can't be saved over it
+fn set_order_locked(order_no: &str, locked: bool, pool: &DbPool) -> Result<(), AppError> {
+    ensure_writable()?;
+    let mut conn = checkout(pool)?;
+    let tx = conn.transaction().map_err(|e| AppError::db("Failed to start transaction", e))?;
+
+    let updated = tx.execute(
+        "UPDATE orders SET locked = ?1, updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now'), version = version + 1 WHERE order_no = ?2",
+        rusqlite::params![locked, order_no],
+    )
+    .map_err(|e| AppError::db("Failed to update order lock", e))?;
+
+    if updated == 0 {
+        return Err(AppError::NotFound(format!("Order {} not found", order_no)));
+    }
+    record_audit(&tx, order_no, if locked { AUDIT_LOCKED } else { AUDIT_UNLOCKED }, "")?;
+
+    tx.commit().map_err(|e| AppError::db("Failed to commit transaction", e))?;
+    Ok(())
+}
+
+// Writes the audit log to a worksheet, oldest first, optionally limited to entries
+// made between two YYYY-MM-DD dates (inclusive, by the shop PC's local date). Changed
+// At is shown in local time. Returns the number of entries written.
+#[tauri::command]
+fn export_audit_log(from: Option<String>, to: Option<String>, file_path: String, pool: tauri::State<DbPool>) -> Result<u32, AppError> {
+    let conn = checkout(&pool)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT order_no, action, datetime(changed_at, 'localtime'), details FROM audit_log
+             WHERE (?1 IS NULL OR date(changed_at, 'localtime') >= ?1)
+               AND (?2 IS NULL OR date(changed_at, 'localtime') <= ?2)
+             ORDER BY changed_at, id",
+        )
+        .map_err(|e| AppError::db("Failed to prepare audit log query", e))?;
+    let entries = stmt
+        .query_map([from, to], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?, row.get::<_, String>(3)?))
+        })
+        .map_err(|e| AppError::db("Failed to query audit log", e))?;
+
+    let mut workbook = rust_xlsxwriter::Workbook::new();
+    let worksheet = workbook.add_worksheet();
+    let header_format = excel_header_format();
+    for (col, header) in ["Order No", "Action", "Changed At", "Details"].iter().enumerate() {
+        worksheet.write_string_with_format(0, col as u16, *header, &header_format)
+            .map_err(|e| AppError::Internal(format!("Failed to write header: {}", e)))?;
+    }
+
+    let mut row_num = 0u32;
+    for entry in entries {
+        let (order_no, action, changed_at, details) = entry.map_err(|e| AppError::db("Failed to parse audit entry", e))?;
+        row_num += 1;
+        for (col, value) in [order_no, action, changed_at, details].iter().enumerate() {
+            worksheet.write_string(row_num, col as u16, value)
+                .map_err(|e| AppError::Internal(format!("Failed to write audit entry: {}", e)))?;
+        }
+    }
+
+    worksheet.autofit();
+    workbook.save(&file_path)
+        .map_err(|e| AppError::Internal(format!("Failed to save Excel file: {}", e)))?;
+
+    Ok(row_num)
+}
+
+// Builds a LIKE pattern matching `text` anywhere, for use with ESCAPE '\'. Order and PO
+// numbers often contain '_' (and sometimes '%'), which would otherwise act as wildcards.
+fn like_contains_pattern(text: &str) -> String {
+    let mut pattern = String::with_capacity(text.len() + 2);
+    pattern.push('%');
+    for c in text.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern.push('%');
+    pattern
+}
+
+// Orders by the customer's own PO number, ignoring case. Exact matches come first,
+// followed by orders whose PO number merely contains the text, newest first within each.
+#[tauri::command]
+fn find_by_buyer_order(buyer_order_no: String, pool: tauri::State<DbPool>) -> Result<Vec<Order>, AppError> {
+    let buyer_order_no = buyer_order_no.trim();
+    if buyer_order_no.is_empty() {
+        return Err(AppError::Validation("Buyer's order number is required".to_string()));
+    }
+
+    let conn = checkout(&pool)?;
+    query_orders(
+        &conn,
+        &format!(
+            "SELECT {} FROM orders
+             WHERE buyer_order_no = ?1 COLLATE NOCASE
+                OR buyer_order_no LIKE ?2 ESCAPE '\\'
+             ORDER BY buyer_order_no = ?1 COLLATE NOCASE DESC, date DESC
+             LIMIT ?3",
+            ORDER_COLUMNS
+        ),
+        rusqlite::params![buyer_order_no, like_contains_pattern(buyer_order_no), MAX_PAGE_SIZE],
+        true,
+    )
+}
+
+#[derive(Debug, Serialize)]
+struct MonthlyAvg {
+    month: String,
+    count: u32,
+    average: f64,
+}
+
+// Average order total per calendar month, grouped like orders_by_month (quotations
+// excluded, malformed dates skipped). Averages are rounded to the paisa.
+#[tauri::command]
+fn avg_order_value_by_month(year: Option<i32>, pool: tauri::State<DbPool>) -> Result<Vec<MonthlyAvg>, AppError> {
+    let conn = checkout(&pool)?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT substr(date, 1, 7) AS month, COUNT(*), ROUND(AVG(total), 2)
+             FROM orders
+             WHERE date GLOB '[0-9][0-9][0-9][0-9]-[0-9][0-9]*'
+               AND doc_type = 'order'
+               AND (?1 IS NULL OR substr(date, 1, 4) = printf('%04d', ?1))
+             GROUP BY month
+             ORDER BY month",
+        )
+        .map_err(|e| AppError::db("Failed to prepare query", e))?;
+
+    let month_iter = stmt
+        .query_map([year], |row| {
+            Ok(MonthlyAvg {
+                month: row.get(0)?,
+                count: row.get(1)?,
+                average: row.get(2)?,
+            })
+        })
+        .map_err(|e| AppError::db("Failed to query monthly averages", e))?;
+
+    let mut months = Vec::new();
+    for month_result in month_iter {
+        months.push(month_result.map_err(|e| AppError::db("Failed to parse monthly average", e))?);
+    }
+
+    Ok(months)
+}
+
+// Optimal string alignment distance: Levenshtein plus adjacent transpositions counted as
+// one edit, since swapped letters ("Rubebr") are the most common typo in names
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut rows = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (rows[i - 1][j] + 1).min(rows[i][j - 1] + 1).min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+// Distinct customer names closest to `query`, ignoring case. A name is scored against
+// both its full text and its first query-length characters, so a partly typed name
+// still ranks well for autocomplete. Names needing more than a third of the query's
+// length in edits are dropped.
+#[tauri::command]
+fn search_customers_fuzzy(query: String, limit: u32, pool: tauri::State<DbPool>) -> Result<Vec<String>, AppError> {
+    let query: Vec<char> = query.trim().to_lowercase().chars().collect();
+    if query.is_empty() {
+        return Ok(Vec::new());
+    }
+    let limit = limit.clamp(1, MAX_RECENT_ORDERS) as usize;
+    let max_distance = (query.len() / 3).max(1);
+
+    let conn = checkout(&pool)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT MIN(TRIM(customer_name)) FROM orders
+             WHERE TRIM(customer_name) != ''
+             GROUP BY TRIM(customer_name) COLLATE NOCASE",
+        )
+        .map_err(|e| AppError::db("Failed to prepare query", e))?;
+    let names = stmt.query_map([], |row| row.get::<_, String>(0))
+        .map_err(|e| AppError::db("Failed to query customers", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| AppError::db("Failed to read customer name", e))?;
+
+    let mut scored: Vec<(usize, String)> = names
+        .into_iter()
+        .filter_map(|name| {
+            let candidate: Vec<char> = name.to_lowercase().chars().collect();
+            let prefix = &candidate[..candidate.len().min(query.len())];
+            let distance = edit_distance(&query, &candidate).min(edit_distance(&query, prefix));
+            (distance <= max_distance).then_some((distance, name))
+        })
+        .collect();
+    scored.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.to_lowercase().cmp(&b.1.to_lowercase())));
+
+    Ok(scored.into_iter().take(limit).map(|(_, name)| name).collect())
+}
+
+// The status that marks an order as finished for cycle-time purposes
+const STATUS_DELIVERED: &str = "Delivered";
+
+#[derive(Debug, Serialize)]
+struct StatusDuration {
+    status: String,
+    // Completed orders that spent measurable time in this status
+    orders: u32,
+    #[serde(rename = "avgHours")]
+    avg_hours: f64,
+}
+
+// Average time completed orders spent in each status before first reaching Delivered,
+// for orders delivered between two YYYY-MM-DD dates (inclusive, local date). Built from
+// the audit log: a status's time runs from the entry that set it (creation or a status
+// change) to the next status change. Orders whose history predates the audit log only
+// count from their first logged change.
+#[tauri::command]
+fn status_duration_report(from: String, to: String, pool: tauri::State<DbPool>) -> Result<Vec<StatusDuration>, AppError> {
+    use chrono::{DateTime, Utc};
+    use std::collections::BTreeMap;
+
+    let (start, end) = parse_date_range(&from, &to)?;
+    let (from, to) = (start.to_string(), end.to_string());
+
+    let conn = checkout(&pool)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT order_no, action, changed_at, details, date(changed_at, 'localtime') FROM audit_log
+             WHERE action IN (?1, ?2)
+             ORDER BY order_no, changed_at, id",
+        )
+        .map_err(|e| AppError::db("Failed to prepare audit log query", e))?;
+    let entries = stmt
+        .query_map([AUDIT_CREATED, AUDIT_STATUS_CHANGED], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, String>(4)?,
+            ))
+        })
+        .map_err(|e| AppError::db("Failed to query audit log", e))?;
+
+    // status -> (orders, total hours)
+    let mut totals: BTreeMap<String, (u32, f64)> = BTreeMap::new();
+    let mut order_no = String::new();
+    let mut current: Option<(String, DateTime<Utc>)> = None;
+    let mut spent: BTreeMap<String, f64> = BTreeMap::new();
+    let mut done = false;
+
+    for entry in entries {
+        let (entry_order, action, changed_at, details, local_date) =
+            entry.map_err(|e| AppError::db("Failed to parse audit entry", e))?;
+        if entry_order != order_no {
+            order_no = entry_order;
+            current = None;
+            spent.clear();
+            done = false;
+        }
+        if done {
+            continue;
+        }
+        let Ok(at) = DateTime::parse_from_rfc3339(&changed_at).map(|t| t.with_timezone(&Utc)) else {
+            continue;
+        };
+
+        let new_status = if action == AUDIT_CREATED {
+            details
+        } else {
+            let Some((old, new)) = details.split_once(AUDIT_STATUS_ARROW) else {
+                continue;
+            };
+            if let Some((status, since)) = &current {
+                if status == old {
+                    let hours = (at - *since).num_seconds() as f64 / 3600.0;
+                    *spent.entry(status.clone()).or_insert(0.0) += hours;
+                }
+            }
+            new.to_string()
+        };
+
+        if new_status == STATUS_DELIVERED && action == AUDIT_STATUS_CHANGED {
+            done = true;
+            if local_date.as_str() >= from.as_str() && local_date.as_str() <= to.as_str() {
+                for (status, hours) in &spent {
+                    let total = totals.entry(status.clone()).or_insert((0, 0.0));
+                    total.0 += 1;
+                    total.1 += hours;
+                }
+            }
+        }
+        current = Some((new_status, at));
+    }
+
+    Ok(totals
+        .into_iter()
+        .map(|(status, (orders, hours))| StatusDuration {
+            status,
+            orders,
+            avg_hours: (hours / orders as f64 * 100.0).round() / 100.0,
+        })
+        .collect())
+}
+
+#[derive(Debug, Serialize)]
+struct OrderIssue {
+    #[serde(rename = "orderNo")]
+    order_no: String,
+    problems: Vec<String>,
+}
+
+// Pre-flight scan of the whole database: every stored order is put through the same
+// checks as save_order, plus item amounts that disagree with qty * rate, and item rows
+// left behind for order numbers that no longer exist. Only orders with problems are
+// listed.
+#[tauri::command]
+fn validate_all_orders(pool: tauri::State<DbPool>) -> Result<Vec<OrderIssue>, AppError> {
+    let conn = checkout(&pool)?;
+    let mut issues = Vec::new();
+
+    for order in load_orders_from_db(&conn)? {
+        let mut problems = order.validate().err().unwrap_or_default();
+        for item in &order.items {
+            let expected_amount = item.expected_amount();
+            if (item.amount - expected_amount).abs() > AMOUNT_TOLERANCE {
+                problems.push(format!(
+                    "Item {} amount {:.2} doesn't match qty x rate ({:.2})",
+                    item.sl_no, item.amount, expected_amount
+                ));
+            }
+        }
+        if !problems.is_empty() {
+            issues.push(OrderIssue { order_no: order.order_no, problems });
+        }
+    }
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT i.order_no, COUNT(*) FROM order_items i
+             LEFT JOIN orders o ON o.order_no = i.order_no
+             WHERE o.order_no IS NULL
+             GROUP BY i.order_no
+             ORDER BY i.order_no",
+        )
+        .map_err(|e| AppError::db("Failed to prepare orphaned items query", e))?;
+    let orphans = stmt
+        .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, u32>(1)?)))
+        .map_err(|e| AppError::db("Failed to query orphaned items", e))?;
+    for orphan in orphans {
+        let (order_no, count) = orphan.map_err(|e| AppError::db("Failed to parse orphaned items", e))?;
+        issues.push(OrderIssue {
+            order_no,
+            problems: vec![format!("{} item(s) belong to an order that doesn't exist", count)],
+        });
+    }
+
+    Ok(issues)
+}
+
+// Corrects a mistyped order number everywhere it's referenced (items, attachments,
+// payments and audit history) in one transaction. Fails if the new number is taken.
+#[tauri::command]
+fn rename_order(old_no: String, new_no: String, pool: tauri::State<DbPool>) -> Result<(), AppError> {
+    ensure_writable()?;
+    let new_no = new_no.trim();
+    if new_no == old_no {
+        return Err(AppError::Validation(format!("Order is already numbered {}", old_no)));
+    }
+
+    let mut conn = checkout(&pool)?;
+    ensure_order_no_format(&conn, new_no)?;
+    let tx = conn.transaction().map_err(|e| AppError::db("Failed to start transaction", e))?;
+    ensure_order_unlocked(&tx, &old_no)?;
+
+    rename_order_number(&tx, &old_no, new_no)?;
+    tx.execute(
+        "UPDATE orders SET printed_at = NULL, updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now'), version = version + 1 WHERE order_no = ?1",
+        [new_no],
+    ).map_err(|e| AppError::db("Failed to update order", e))?;
+    record_audit(&tx, new_no, AUDIT_RENAMED, &format!("from {}", old_no))?;
+    stamp_invoice_hash(&tx, new_no)?;
+
+    tx.commit().map_err(|e| AppError::db("Failed to commit transaction", e))?;
+    Ok(())
+}
+
+#[derive(Debug, Serialize)]
+struct PendingItem {
+    #[serde(flatten)]
+    item: OrderItem,
+    #[serde(rename = "orderNo")]
+    order_no: String,
+    #[serde(rename = "customerName")]
+    customer_name: String,
+    date: String,
+    status: String,
+    #[serde(rename = "machineName")]
+    machine_name: String,
+}
+
+#[derive(Serialize)]
+struct PaginatedItems {
+    items: Vec<PendingItem>,
+    total: u32,
+    page: u32,
+    #[serde(rename = "pageSize")]
+    page_size: u32,
+    #[serde(rename = "totalPages")]
+    total_pages: u32,
+}
+
+// Line items still to be made (orders in a PENDING_STATUSES status; quotations aren't
+// production work), oldest order first for planning the day. The machine filter is
+// compared trimmed and case-insensitively.
+#[tauri::command]
+fn load_pending_items(page: Option<u32>, page_size: Option<u32>, machine: Option<String>, pool: tauri::State<DbPool>) -> Result<PaginatedItems, AppError> {
+    let (page, page_size) = clamp_pagination(page, page_size);
+    let machine = machine.map(|m| m.trim().to_string()).filter(|m| !m.is_empty());
+    let conn = checkout(&pool)?;
+
+    let filter = "FROM order_items i
+         JOIN orders o ON o.order_no = i.order_no
+         WHERE o.doc_type = 'order'
+           AND o.status IN (?1, ?2)
+           AND (?3 IS NULL OR TRIM(o.machine_name) = ?3 COLLATE NOCASE)";
+
+    let total: u32 = conn
+        .query_row(
+            &format!("SELECT COUNT(*) {}", filter),
+            rusqlite::params![PENDING_STATUSES[0], PENDING_STATUSES[1], machine],
+            |row| row.get(0),
+        )
+        .map_err(|e| AppError::db("Failed to count pending items", e))?;
+
+    let columns = prefixed_item_columns("i");
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {}, o.order_no, o.customer_name, o.date, o.status, o.machine_name {}
+             ORDER BY o.date, o.order_no, i.sl_no
+             LIMIT ?4 OFFSET ?5",
+            columns, filter
+        ))
+        .map_err(|e| AppError::db("Failed to prepare pending items query", e))?;
+
+    let item_iter = stmt
+        .query_map(
+            rusqlite::params![PENDING_STATUSES[0], PENDING_STATUSES[1], machine, page_size, page_offset(page, page_size)],
+            |row| {
+                Ok(PendingItem {
+                    item: item_from_row(row)?,
+                    order_no: row.get(10)?,
+                    customer_name: row.get(11)?,
+                    date: row.get(12)?,
+                    status: row.get(13)?,
+                    machine_name: row.get(14).unwrap_or_default(),
+                })
+            },
+        )
+        .map_err(|e| AppError::db("Failed to query pending items", e))?;
+
+    let mut items = Vec::new();
+    for item_result in item_iter {
+        items.push(item_result.map_err(|e| AppError::db("Failed to parse pending item", e))?);
+    }
+
+    Ok(PaginatedItems {
+        items,
+        total,
+        page,
+        page_size,
+        total_pages: (total as f64 / page_size as f64).ceil() as u32,
+    })
+}
+
+// Higher numbers are more urgent; 0 is normal. Priority is shop-floor scheduling rather
+// than order content, so it's set here instead of through save_order, and doesn't bump
+// the order's version (an open edit form stays saveable).
+#[tauri::command]
+fn set_priority(order_no: String, priority: i32, pool: tauri::State<DbPool>) -> Result<(), AppError> {
+    ensure_writable()?;
+    let conn = checkout(&pool)?;
+
+    let updated = conn.execute(
+        "UPDATE orders SET priority = ?1, updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') WHERE order_no = ?2",
+        rusqlite::params![priority, order_no],
+    )
+    .map_err(|e| AppError::db("Failed to update priority", e))?;
+
+    if updated == 0 {
+        return Err(AppError::NotFound(format!("Order {} not found", order_no)));
+    }
+
+    Ok(())
+}
+
+// Folds the -wal sidecar back into orders.db and truncates it, so a plain copy of
+// orders.db holds everything committed so far
+fn checkpoint(conn: &Connection) -> Result<(), AppError> {
+    let busy: i64 = conn
+        .query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |row| row.get(0))
+        .map_err(|e| AppError::db("Failed to checkpoint database", e))?;
+    if busy != 0 {
+        return Err(AppError::Locked("Database is busy; close other windows using it and try again".to_string()));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+fn checkpoint_wal(pool: tauri::State<DbPool>) -> Result<(), AppError> {
+    let conn = checkout(&pool)?;
+    checkpoint(&conn)
+}
+
+// Writes a consistent, compacted copy of the database to `file_path` (which must not
+// exist yet), checkpointing first so the live orders.db is also complete on disk
+#[tauri::command]
+fn backup_database(file_path: String, pool: tauri::State<DbPool>) -> Result<(), AppError> {
+    if std::path::Path::new(&file_path).exists() {
+        return Err(AppError::Conflict(format!("{} already exists", file_path)));
+    }
+
+    let conn = checkout(&pool)?;
+    checkpoint(&conn)?;
+    conn.execute("VACUUM INTO ?1", [&file_path])
+        .map_err(|e| AppError::db("Failed to back up database", e))?;
+    Ok(())
+}
+
+// SHA-256 (hex) over what the invoice shows: header, parties, references, items and
+// money fields. Workflow state (status, machine, priority, lock, payments, version,
+// timestamps) is left out so it can change without looking like tampering. The fields
+// go through a serde_json map, whose keys are sorted, so the input is canonical.
+fn compute_invoice_hash(order: &Order) -> String {
+    use sha2::{Digest, Sha256};
+
+    let money = |amount: f64| format!("{:.2}", amount);
+    let items: Vec<serde_json::Value> = order
+        .items
+        .iter()
+        .map(|item| {
+            serde_json::json!({
+                "slNo": item.sl_no,
+                "type": item.item_type,
+                "hsn": item.hsn,
+                "qty": item.qty,
+                "length": item.length,
+                "dia": item.dia,
+                "shore": item.shore,
+                "remarks": item.remarks,
+                "rate": money(item.rate),
+                "amount": money(item.amount),
+            })
+        })
+        .collect();
+    let canonical = serde_json::json!({
+        "orderNo": order.order_no,
+        "docType": order.doc_type,
+        "date": order.date,
+        "customerName": order.customer_name,
+        "contactPerson": order.contact_person,
+        "phone": order.phone,
+        "gstin": order.gstin,
+        "remarks": order.remarks,
+        "deliveryNote": order.delivery_note,
+        "deliveryNoteDate": order.delivery_note_date,
+        "buyerOrderNo": order.buyer_order_no,
+        "buyerOrderDate": order.buyer_order_date,
+        "items": items,
+        "subtotal": money(order.subtotal),
+        "discountType": order.discount_type,
+        "discountValue": order.discount_value,
+        "discountAmount": money(order.discount_amount),
+        "gstRate": order.gst_rate,
+        "gst": money(order.gst),
+        "total": money(order.total),
+    });
+
+    format!("{:x}", Sha256::digest(canonical.to_string().as_bytes()))
+}
+
+// Recomputes the hash from the order as stored, so it covers exactly what a later
+// verify_order_hash will read back. Called by everything that changes hashed fields.
+fn stamp_invoice_hash(tx: &rusqlite::Transaction, order_no: &str) -> Result<(), AppError> {
+    let order = load_order_by_no(tx, order_no)?
+        .ok_or_else(|| AppError::NotFound(format!("Order {} not found", order_no)))?;
+    tx.execute(
+        "UPDATE orders SET invoice_hash = ?1 WHERE order_no = ?2",
+        [compute_invoice_hash(&order), order.order_no],
+    ).map_err(|e| AppError::db("Failed to store invoice hash", e))?;
+    Ok(())
+}
+
+#[derive(Debug, Serialize)]
+struct HashVerification {
+    #[serde(rename = "orderNo")]
+    order_no: String,
+    // None for orders not saved since hashing was introduced
+    #[serde(rename = "storedHash")]
+    stored_hash: Option<String>,
+    #[serde(rename = "computedHash")]
+    computed_hash: String,
+    matches: bool,
+}
+
+// Tamper check: were the order's invoice fields changed outside the app since it last
+// stamped the hash?
+#[tauri::command]
+fn verify_order_hash(order_no: String, pool: tauri::State<DbPool>) -> Result<HashVerification, AppError> {
+    let conn = checkout(&pool)?;
+    let order = load_order_by_no(&conn, &order_no)?
+        .ok_or_else(|| AppError::NotFound(format!("Order {} not found", order_no)))?;
+    let stored: String = conn
+        .query_row("SELECT invoice_hash FROM orders WHERE order_no = ?1", [&order_no], |row| row.get(0))
+        .map_err(|e| AppError::db("Failed to load invoice hash", e))?;
+
+    let computed_hash = compute_invoice_hash(&order);
+    let stored_hash = Some(stored).filter(|hash| !hash.is_empty());
+    Ok(HashVerification {
+        order_no,
+        matches: stored_hash.as_deref() == Some(computed_hash.as_str()),
+        stored_hash,
+        computed_hash,
+    })
+}
+
+#[derive(Debug, Serialize)]
+struct PendingOrder {
+    #[serde(flatten)]
+    order: Order,
+    #[serde(rename = "daysPending")]
+    days_pending: i64,
+}
+
+// Pending orders (see PENDING_STATUSES) oldest first, for chasing stale jobs. Days are
+// counted from the order date to today in local time; like outstanding_orders, an
+// unparseable date counts as 0. Items are omitted as in recent_orders.
+#[tauri::command]
+fn oldest_pending(limit: u32, pool: tauri::State<DbPool>) -> Result<Vec<PendingOrder>, AppError> {
+    let limit = limit.clamp(1, MAX_RECENT_ORDERS);
+    let conn = checkout(&pool)?;
+
+    let orders = query_orders(
+        &conn,
+        &format!(
+            "SELECT {} FROM orders
+             WHERE doc_type = 'order' AND status IN (?1, ?2)
+             ORDER BY date, order_no
+             LIMIT ?3",
+            ORDER_COLUMNS
+        ),
+        rusqlite::params![PENDING_STATUSES[0], PENDING_STATUSES[1], limit],
+        false,
+    )?;
+
+    let today = chrono::Local::now().date_naive();
+    Ok(orders
+        .into_iter()
+        .map(|order| {
+            let days_pending = chrono::NaiveDate::parse_from_str(&order.date, "%Y-%m-%d")
+                .map_or(0, |date| (today - date).num_days().max(0));
+            PendingOrder { order, days_pending }
+        })
+        .collect())
+}
+
+// Change feed for one-way sync: orders written after `seq`, in write order, with items.
+// At most MAX_PAGE_SIZE are returned per call; keep calling with the last changeSeq
+// received until the result is empty. Deletions don't appear here (the row is gone);
+// the audit log has them.
+#[tauri::command]
+fn changes_since(seq: i64, pool: tauri::State<DbPool>) -> Result<Vec<Order>, AppError> {
+    let conn = checkout(&pool)?;
+
+    query_orders(
+        &conn,
+        &format!(
+            "SELECT {} FROM orders WHERE change_seq > ?1 ORDER BY change_seq LIMIT ?2",
+            ORDER_COLUMNS
+        ),
+        rusqlite::params![seq, MAX_PAGE_SIZE],
+        true,
+    )
+}
+
+#[derive(Debug, Serialize)]
+struct MergeResult {
+    inserted: u32,
+    // Same order number, customer, date and total on both sides: already merged
+    #[serde(rename = "skippedDuplicates")]
+    skipped_duplicates: u32,
+    // Same order number but different contents; left untouched for someone to resolve
+    conflicts: Vec<String>,
+}
+
+fn table_columns(conn: &Connection, schema: &str, table: &str) -> SqlResult<Vec<String>> {
+    let mut stmt = conn.prepare(&format!("PRAGMA {}.table_info({})", schema, table))?;
+    let columns = stmt.query_map([], |row| row.get::<_, String>(1))?;
+    columns.collect()
+}
+
+// Columns present in both main's and an attached database's copy of a table, minus ones
+// the target fills in itself
+fn shared_columns(conn: &Connection, schema: &str, table: &str, skip: &[&str]) -> Result<String, AppError> {
+    let ours = table_columns(conn, "main", table).map_err(|e| AppError::db("Failed to read table columns", e))?;
+    let theirs = table_columns(conn, schema, table).map_err(|e| AppError::db("Failed to read table columns", e))?;
+    Ok(ours
+        .into_iter()
+        .filter(|c| theirs.contains(c) && !skip.contains(&c.as_str()))
+        .collect::<Vec<_>>()
+        .join(", "))
+}
+
+// Copies orders (with their items and payments) from another location's database that
+// this one doesn't have, all in one transaction. The other file is only read, never
+// migrated, so it may be on an older schema: just the columns both sides share are
+// copied. Existing order numbers are never overwritten; they're counted as duplicates
+// or reported as conflicts.
+#[tauri::command]
+fn merge_database(other_path: String, pool: tauri::State<DbPool>) -> Result<MergeResult, AppError> {
+    ensure_writable()?;
+    let other = std::path::Path::new(&other_path);
+    if !other.is_file() {
+        return Err(AppError::NotFound(format!("{} not found", other_path)));
+    }
+    if let (Ok(a), Ok(b)) = (other.canonicalize(), get_db_path().canonicalize()) {
+        if a == b {
+            return Err(AppError::Validation("Cannot merge the database into itself".to_string()));
+        }
+    }
+
+    let mut conn = checkout(&pool)?;
+    // ATTACH isn't allowed inside a transaction, and the pooled connection must not stay
+    // attached whatever happens, so detach before looking at the merge's outcome
+    conn.execute("ATTACH DATABASE ?1 AS other", [&other_path])
+        .map_err(|e| AppError::db("Failed to open other database", e))?;
+    let result = merge_attached(&mut conn, &other_path);
+    conn.execute("DETACH DATABASE other", [])
+        .map_err(|e| AppError::db("Failed to close other database", e))?;
+    result
+}
+
+fn merge_attached(conn: &mut Connection, other_path: &str) -> Result<MergeResult, AppError> {
+    let has_orders: bool = conn
+        .query_row("SELECT COUNT(*) > 0 FROM other.sqlite_master WHERE type = 'table' AND name = 'orders'", [], |row| row.get(0))
+        .map_err(|e| AppError::db("Failed to read other database", e))?;
+    if !has_orders {
+        return Err(AppError::Validation(format!("{} is not an orders database", other_path)));
+    }
+    let has_payments: bool = conn
+        .query_row("SELECT COUNT(*) > 0 FROM other.sqlite_master WHERE type = 'table' AND name = 'payments'", [], |row| row.get(0))
+        .map_err(|e| AppError::db("Failed to read other database", e))?;
+
+    let order_columns = shared_columns(conn, "other", "orders", &["change_seq", "invoice_hash"])?;
+    let item_columns = shared_columns(conn, "other", "order_items", &["id"])?;
+    let payment_columns = if has_payments { shared_columns(conn, "other", "payments", &["id"])? } else { String::new() };
+
+    let tx = conn.transaction().map_err(|e| AppError::db("Failed to start transaction", e))?;
+
+    let (mut skipped_duplicates, mut conflicts) = (0u32, Vec::new());
+    {
+        let mut stmt = tx
+            .prepare(
+                "SELECT o.order_no, o.customer_name = m.customer_name AND o.date = m.date AND ABS(o.total - m.total) < 0.005
+                 FROM other.orders o JOIN main.orders m ON m.order_no = o.order_no
+                 ORDER BY o.order_no",
+            )
+            .map_err(|e| AppError::db("Failed to prepare query", e))?;
+        let clashes = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, bool>(1)?)))
+            .map_err(|e| AppError::db("Failed to compare orders", e))?;
+        for clash in clashes {
+            let (order_no, same) = clash.map_err(|e| AppError::db("Failed to compare orders", e))?;
+            if same {
+                skipped_duplicates += 1;
+            } else {
+                conflicts.push(order_no);
+            }
+        }
+    }
+
+    let new_orders = {
+        let mut stmt = tx
+            .prepare("SELECT order_no FROM other.orders WHERE order_no NOT IN (SELECT order_no FROM main.orders) ORDER BY order_no")
+            .map_err(|e| AppError::db("Failed to prepare query", e))?;
+        let rows = stmt
+            .query_map([], |row| row.get::<_, String>(0))
+            .map_err(|e| AppError::db("Failed to query other orders", e))?;
+        rows.collect::<SqlResult<Vec<_>>>()
+            .map_err(|e| AppError::db("Failed to parse order number", e))?
+    };
+
+    let details = format!("from {}", other_path);
+    for order_no in &new_orders {
+        tx.execute(
+            &format!("INSERT INTO main.orders ({0}) SELECT {0} FROM other.orders WHERE order_no = ?1", order_columns),
+            [order_no],
+        ).map_err(|e| AppError::db("Failed to copy order", e))?;
+        tx.execute(
+            &format!("INSERT INTO main.order_items ({0}) SELECT {0} FROM other.order_items WHERE order_no = ?1 ORDER BY sl_no, id", item_columns),
+            [order_no],
+        ).map_err(|e| AppError::db("Failed to copy order items", e))?;
+        if has_payments {
+            tx.execute(
+                &format!("INSERT INTO main.payments ({0}) SELECT {0} FROM other.payments WHERE order_no = ?1 ORDER BY id", payment_columns),
+                [order_no],
+            ).map_err(|e| AppError::db("Failed to copy payments", e))?;
+        }
+
+        // Derived columns the other side may predate
+        let phone: String = tx
+            .query_row("SELECT COALESCE(phone, '') FROM main.orders WHERE order_no = ?1", [order_no], |row| row.get(0))
+            .map_err(|e| AppError::db("Failed to read phone", e))?;
+        tx.execute(
+            "UPDATE main.orders SET phone_normalized = ?1 WHERE order_no = ?2",
+            [normalize_phone(&phone), order_no.clone()],
+        ).map_err(|e| AppError::db("Failed to update phone", e))?;
+        if has_payments {
+            refresh_amount_paid(&tx, order_no)?;
+        }
+        refresh_payment_status(&tx, order_no)?;
+        stamp_invoice_hash(&tx, order_no)?;
+        record_audit(&tx, order_no, AUDIT_MERGED, &details)?;
+    }
+
+    tx.commit().map_err(|e| AppError::db("Failed to commit transaction", e))?;
+
+    Ok(MergeResult {
+        inserted: new_orders.len() as u32,
+        skipped_duplicates,
+        conflicts,
+    })
+}
+
+// Our own state code for place-of-supply when the company profile has no GSTIN yet:
+// the works are in Hyderabad, Telangana
+const DEFAULT_STATE_CODE: &str = "36";
+
+// The state code a GSTIN starts with, or None when it's blank or not a valid GSTIN.
+// Rows from before validation or from merge_database may hold anything, so nothing is
+// sliced out of a GSTIN that hasn't passed validate_gstin.
+fn gstin_state_code(gstin: &str) -> Option<&str> {
+    validate_gstin(gstin).ok()?;
+    gstin.get(..2)
+}
+
+fn company_state_code(company: &CompanyProfile) -> &str {
+    gstin_state_code(&company.gstin).unwrap_or(DEFAULT_STATE_CODE)
+}
+
+fn round2(amount: f64) -> f64 {
+    (amount * 100.0).round() / 100.0
+}
+
+// Splits GST into the portal's (IGST, CGST, SGST) amounts: inter-state supplies carry
+// IGST, intra-state ones CGST and SGST in equal halves
+fn gst_split(gst: f64, inter_state: bool) -> (f64, f64, f64) {
+    if inter_state {
+        (round2(gst), 0.0, 0.0)
+    } else {
+        let half = round2(gst / 2.0);
+        (0.0, half, round2(gst - half))
+    }
+}
+
+// GSTR-1 JSON for one month (YYYY-MM) in the portal's offline-tool layout. Orders with a
+// customer GSTIN go under b2b, one invoice each, grouped by customer; the rest are
+// summed into b2cs by supply type and rate. Place of supply is the customer GSTIN's
+// state, or our own state for unregistered buyers, whose addresses aren't recorded;
+// for the same reason large inter-state B2C invoices (B2CL) aren't separated out.
+// Quotations and rejected orders are excluded as in gst_summary. Orders whose GSTIN
+// isn't valid are reported instead of guessing a place of supply for them.
+#[tauri::command]
+fn export_gstr1(month: String, file_path: String, pool: tauri::State<DbPool>) -> Result<(), AppError> {
+    use std::collections::BTreeMap;
+
+    let period = chrono::NaiveDate::parse_from_str(&format!("{}-01", month.trim()), "%Y-%m-%d")
+        .map_err(|_| AppError::Validation(format!("Invalid month '{}' (expected YYYY-MM)", month)))?;
+
+    let conn = checkout(&pool)?;
+    let company = load_company_profile(&conn)?;
+    let our_state = company_state_code(&company);
+
+    let orders = query_orders(
+        &conn,
+        &format!(
+            "SELECT {} FROM orders
+             WHERE substr(date, 1, 7) = ?1 AND doc_type = 'order' AND status != 'Rejected'
+             ORDER BY date, order_no",
+            ORDER_COLUMNS
+        ),
+        [period.format("%Y-%m").to_string()],
+        false,
+    )?;
+
+    let invalid: Vec<&str> = orders
+        .iter()
+        .filter(|o| !o.gstin.is_empty() && gstin_state_code(&o.gstin).is_none())
+        .map(|o| o.order_no.as_str())
+        .collect();
+    if !invalid.is_empty() {
+        return Err(AppError::Validation(format!(
+            "Fix the customer GSTIN on order(s) {} before exporting GSTR-1",
+            invalid.join(", ")
+        )));
+    }
+
+    let mut b2b: BTreeMap<String, Vec<serde_json::Value>> = BTreeMap::new();
+    // (supply type, place of supply, rate in paise-percent) -> (taxable value, gst)
+    let mut b2cs: BTreeMap<(&str, String, i64), (f64, f64)> = BTreeMap::new();
+
+    for order in &orders {
+        let taxable = round2(order.subtotal - order.discount_amount);
+        let pos = gstin_state_code(&order.gstin).unwrap_or(our_state).to_string();
+        let inter_state = pos != our_state;
+
+        if order.gstin.is_empty() {
+            let key = (if inter_state { "INTER" } else { "INTRA" }, pos, (order.gst_rate * 100.0).round() as i64);
+            let bucket = b2cs.entry(key).or_insert((0.0, 0.0));
+            bucket.0 += taxable;
+            bucket.1 += order.gst;
+            continue;
+        }
+
+        let (iamt, camt, samt) = gst_split(order.gst, inter_state);
+        let invoice_date = chrono::NaiveDate::parse_from_str(&order.date, "%Y-%m-%d")
+            .map(|d| d.format("%d-%m-%Y").to_string())
+            .unwrap_or_else(|_| order.date.clone());
+        b2b.entry(order.gstin.clone()).or_default().push(serde_json::json!({
+            "inum": order.order_no,
+            "idt": invoice_date,
+            "val": round2(order.total),
+            "pos": pos,
+            "rchrg": "N",
+            "inv_typ": "R",
+            "itms": [{
+                "num": 1,
+                "itm_det": {
+                    "txval": taxable,
+                    "rt": order.gst_rate,
+                    "iamt": iamt,
+                    "camt": camt,
+                    "samt": samt,
+                    "csamt": 0.0,
+                },
+            }],
+        }));
+    }
+
+    let b2b: Vec<serde_json::Value> = b2b
+        .into_iter()
+        .map(|(ctin, invoices)| serde_json::json!({ "ctin": ctin, "inv": invoices }))
+        .collect();
+    let b2cs: Vec<serde_json::Value> = b2cs
+        .into_iter()
+        .map(|((supply_type, pos, rate), (taxable, gst))| {
+            let (iamt, camt, samt) = gst_split(gst, supply_type == "INTER");
+            serde_json::json!({
+                "sply_ty": supply_type,
+                "pos": pos,
+                "typ": "OE",
+                "rt": rate as f64 / 100.0,
+                "txval": round2(taxable),
+                "iamt": iamt,
+                "camt": camt,
+                "samt": samt,
+                "csamt": 0.0,
+            })
+        })
+        .collect();
+
+    let gstr1 = serde_json::json!({
+        "gstin": company.gstin,
+        "fp": period.format("%m%Y").to_string(),
+        "b2b": b2b,
+        "b2cs": b2cs,
+    });
+
+    let json = serde_json::to_string_pretty(&gstr1)
+        .map_err(|e| AppError::Internal(format!("Failed to serialize GSTR-1: {}", e)))?;
+    std::fs::write(&file_path, json)
+        .map_err(|e| AppError::Internal(format!("Failed to save JSON file: {}", e)))?;
+
+    Ok(())
+}
+
+// Startup check for the frontend. init_pool only logs when the database can't be set
+// up (e.g. the app sits in a folder it can't write to, like Program Files), so this
+// opens it directly and proves a write works, rolling the write back, to give the user
+// an error they can act on. Read-only terminals only need to be able to read.
+#[tauri::command]
+fn db_health_check() -> Result<(), AppError> {
+    let db_path = get_db_path();
+    let location = db_path.display();
+    let advice = "Move the AAKSO folder somewhere your account can write to (e.g. Documents or D:\\), then start the app from there";
+
+    let mut conn = Connection::open(&db_path)
+        .map_err(|e| AppError::Internal(format!("Cannot open the database at {}: {}. {}", location, e, advice)))?;
+    configure_connection(&conn)
+        .map_err(|e| AppError::Internal(format!("Cannot open the database at {}: {}. {}", location, e, advice)))?;
+
+    let schema_version = current_schema_version(&conn)
+        .map_err(|e| AppError::Internal(format!("The database at {} couldn't be set up: {}. {}", location, e, advice)))?;
+    let latest = MIGRATIONS.last().map_or(0, |m| m.version);
+    if schema_version < latest {
+        return Err(AppError::Internal(format!(
+            "The database at {} is at schema version {} but this app needs {}; it couldn't be upgraded. {}",
+            location, schema_version, latest, advice
+        )));
+    }
+
+    if read_only_mode() {
+        return Ok(());
+    }
+
+    let tx = conn.transaction()
+        .map_err(|e| AppError::db("Failed to start transaction", e))?;
+    tx.execute(
+        "INSERT OR REPLACE INTO settings (key, value) VALUES ('health_check', strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))",
+        [],
+    )
+    .map_err(|e| AppError::Internal(format!("The database at {} is read-only: {}. {}", location, e, advice)))?;
+    tx.rollback()
+        .map_err(|e| AppError::db("Failed to roll back health check", e))?;
+
+    Ok(())
+}
+
+// Moves orders dated before `date` (YYYY-MM-DD) into an archive database, creating it
+// if needed, together with their items, payments, attachments and audit history. The
+// copy and the delete run in one transaction; the main database keeps an "archived"
+// audit entry per order. SQLite only guarantees atomicity per file in WAL mode, so a
+// crash mid-commit can at worst leave an order in both files, never in neither.
+#[tauri::command]
+fn archive_orders_before(date: String, archive_path: String, pool: tauri::State<DbPool>) -> Result<u32, AppError> {
+    ensure_writable()?;
+    let cutoff = chrono::NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+        .map_err(|_| AppError::Validation(format!("Invalid date '{}' (expected YYYY-MM-DD)", date)))?
+        .format("%Y-%m-%d")
+        .to_string();
+    let archive = std::path::Path::new(&archive_path);
+    if let (Ok(a), Ok(b)) = (archive.canonicalize(), get_db_path().canonicalize()) {
+        if a == b {
+            return Err(AppError::Validation("Choose a different file to archive into".to_string()));
+        }
+    }
+
+    // Brings a new or older archive file up to the current schema
+    open_database(archive).map_err(|e| AppError::db("Failed to open archive database", e))?;
+
+    let mut conn = checkout(&pool)?;
+    conn.execute("ATTACH DATABASE ?1 AS archive", [&archive_path])
+        .map_err(|e| AppError::db("Failed to open archive database", e))?;
+    let result = archive_attached(&mut conn, &cutoff, &archive_path);
+    conn.execute("DETACH DATABASE archive", [])
+        .map_err(|e| AppError::db("Failed to close archive database", e))?;
+    result
+}
+
+fn archive_attached(conn: &mut Connection, cutoff: &str, archive_path: &str) -> Result<u32, AppError> {
+    let order_columns = shared_columns(conn, "archive", "orders", &["change_seq"])?;
+    let tx = conn.transaction().map_err(|e| AppError::db("Failed to start transaction", e))?;
+
+    let clashes: u32 = tx
+        .query_row(
+            "SELECT COUNT(*) FROM main.orders WHERE date < ?1 AND order_no IN (SELECT order_no FROM archive.orders)",
+            [cutoff],
+            |row| row.get(0),
+        )
+        .map_err(|e| AppError::db("Failed to check archive", e))?;
+    if clashes > 0 {
+        return Err(AppError::Conflict(format!("{} of these order number(s) are already in the archive", clashes)));
+    }
+
+    // For each table: (name, columns to copy). Orders go first for the foreign keys.
+    let tables = [
+        ("orders", order_columns),
+        ("order_items", shared_columns(&tx, "archive", "order_items", &["id"])?),
+        ("payments", shared_columns(&tx, "archive", "payments", &["id"])?),
+        ("order_attachments", shared_columns(&tx, "archive", "order_attachments", &["id"])?),
+        ("audit_log", shared_columns(&tx, "archive", "audit_log", &["id"])?),
+    ];
+    for (table, columns) in &tables {
+        tx.execute(
+            &format!(
+                "INSERT INTO archive.{0} ({1}) SELECT {1} FROM main.{0}
+                 WHERE order_no IN (SELECT order_no FROM main.orders WHERE date < ?1)",
+                table, columns
+            ),
+            [cutoff],
+        ).map_err(|e| AppError::db(&format!("Failed to archive {}", table), e))?;
+    }
+
+    tx.execute(
+        "INSERT INTO main.audit_log (order_no, action, changed_at, details)
+         SELECT order_no, ?1, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'), ?2 FROM main.orders WHERE date < ?3",
+        [AUDIT_ARCHIVED, &format!("to {}", archive_path), cutoff],
+    ).map_err(|e| AppError::db("Failed to record audit entries", e))?;
+
+    // Items, payments and attachments go with their orders via ON DELETE CASCADE
+    let archived = tx.execute("DELETE FROM main.orders WHERE date < ?1", [cutoff])
+        .map_err(|e| AppError::db("Failed to remove archived orders", e))?;
+
+    tx.commit().map_err(|e| AppError::db("Failed to commit transaction", e))?;
+    Ok(archived as u32)
+}
+
+// Writes orders dated within from..=to, with their items and payments, to a new database
+// file at the current schema so it opens in the app as-is (e.g. to hand a quarter to the
+// accountant). Payments go along because amount_paid is recomputed from them. Nothing is
+// removed from the main database. The file must not exist yet; if the copy fails it's
+// deleted again rather than left half-filled.
+#[tauri::command]
+fn export_subset_db(from: String, to: String, dest_path: String, pool: tauri::State<DbPool>) -> Result<u32, AppError> {
+    let (start, end) = parse_date_range(&from, &to)?;
+    let dest = std::path::Path::new(&dest_path);
+    if dest.exists() {
+        return Err(AppError::Conflict(format!("{} already exists", dest_path)));
+    }
+
+    open_database(dest).map_err(|e| AppError::db("Failed to create subset database", e))?;
+
+    let mut conn = checkout(&pool)?;
+    let result = conn
+        .execute("ATTACH DATABASE ?1 AS subset", [&dest_path])
+        .map_err(|e| AppError::db("Failed to open subset database", e))
+        .and_then(|_| {
+            let copied = copy_subset_attached(&mut conn, &start.to_string(), &end.to_string());
+            conn.execute("DETACH DATABASE subset", [])
+                .map_err(|e| AppError::db("Failed to close subset database", e))?;
+            copied
+        });
+    if result.is_err() {
+        let _ = std::fs::remove_file(dest);
+    }
+    result
+}
+
+fn copy_subset_attached(conn: &mut Connection, from: &str, to: &str) -> Result<u32, AppError> {
+    let tables = [
+        ("orders", shared_columns(conn, "subset", "orders", &["change_seq"])?),
+        ("order_items", shared_columns(conn, "subset", "order_items", &["id"])?),
+        ("payments", shared_columns(conn, "subset", "payments", &["id"])?),
+    ];
+    let tx = conn.transaction().map_err(|e| AppError::db("Failed to start transaction", e))?;
+
+    let mut copied = 0;
+    for (table, columns) in &tables {
+        let rows = tx.execute(
+            &format!(
+                "INSERT INTO subset.{0} ({1}) SELECT {1} FROM main.{0}
+                 WHERE order_no IN (SELECT order_no FROM main.orders WHERE date >= ?1 AND date <= ?2)",
+                table, columns
+            ),
+            [from, to],
+        ).map_err(|e| AppError::db(&format!("Failed to copy {}", table), e))?;
+        if *table == "orders" {
+            copied = rows as u32;
+        }
+    }
+
+    tx.commit().map_err(|e| AppError::db("Failed to commit transaction", e))?;
+    Ok(copied)
+}
+
+// Orders whose number, customer name or buyer's PO number contains the text (ignoring
+// case), or whose phone contains the same digits. Newest first, capped at MAX_PAGE_SIZE.
+fn search_orders_in(conn: &Connection, query: &str) -> Result<Vec<Order>, AppError> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Err(AppError::Validation("Search text is required".to_string()));
+    }
+    let digits: String = query.chars().filter(|c| c.is_ascii_digit()).collect();
+    // A couple of digits would match nearly every phone number
+    let phone_pattern = (digits.len() >= 4).then(|| like_contains_pattern(&digits));
+
+    query_orders(
+        conn,
+        &format!(
+            "SELECT {} FROM orders
+             WHERE order_no LIKE ?1 ESCAPE '\\'
+                OR customer_name LIKE ?1 ESCAPE '\\'
+                OR buyer_order_no LIKE ?1 ESCAPE '\\'
+                OR (?2 IS NOT NULL AND phone_normalized LIKE ?2 ESCAPE '\\')
+             ORDER BY date DESC, order_no DESC
+             LIMIT ?3",
+            ORDER_COLUMNS
+        ),
+        rusqlite::params![like_contains_pattern(query), phone_pattern, MAX_PAGE_SIZE],
+        true,
+    )
+}
+
+#[tauri::command]
+fn search_orders(query: String, pool: tauri::State<DbPool>) -> Result<Vec<Order>, AppError> {
+    let conn = checkout(&pool)?;
+    search_orders_in(&conn, &query)
+}
+
+// Same search as search_orders, run against an archive file made by archive_orders_before.
+// The file is opened read-only, so it has to be at the current schema version already;
+// archiving into it again brings it up to date.
+#[tauri::command]
+fn search_archive(archive_path: String, query: String) -> Result<Vec<Order>, AppError> {
+    let conn = Connection::open_with_flags(
+        &archive_path,
+        rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY | rusqlite::OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    ).map_err(|e| AppError::db("Failed to open archive database", e))?;
+
+    let schema_version = current_schema_version(&conn)
+        .map_err(|e| AppError::db("Failed to read archive schema version", e))?;
+    let latest = MIGRATIONS.last().map_or(0, |m| m.version);
+    if schema_version != latest {
+        return Err(AppError::Validation(format!(
+            "The archive is at schema version {} but this app uses {}",
+            schema_version, latest
+        )));
+    }
+
+    search_orders_in(&conn, &query)
+}
+
+#[derive(Debug, Serialize)]
+struct TypeRevenue {
+    #[serde(rename = "itemType")]
+    item_type: String,
+    qty: f64,
+    revenue: f64,
+}
+
+// Item amounts summed per item type, optionally within an inclusive YYYY-MM-DD order date
+// range, largest first. Types are grouped ignoring case and surrounding spaces; quotations
+// don't count. Revenue is before GST and order-level discounts.
+#[tauri::command]
+fn revenue_by_item_type(from: Option<String>, to: Option<String>, pool: tauri::State<DbPool>) -> Result<Vec<TypeRevenue>, AppError> {
+    let conn = checkout(&pool)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT MIN(TRIM(i.item_type)), SUM(i.qty), ROUND(SUM(i.amount), 2) AS revenue
+             FROM order_items i
+             JOIN orders o ON o.order_no = i.order_no
+             WHERE o.doc_type = 'order'
+               AND (?1 IS NULL OR o.date >= ?1)
+               AND (?2 IS NULL OR o.date <= ?2)
+             GROUP BY LOWER(TRIM(COALESCE(i.item_type, '')))
+             ORDER BY revenue DESC",
+        )
+        .map_err(|e| AppError::db("Failed to prepare revenue query", e))?;
+
+    let rows = stmt
+        .query_map(rusqlite::params![from, to], |row| {
+            Ok(TypeRevenue {
+                item_type: row.get::<_, Option<String>>(0)?.unwrap_or_default(),
+                qty: row.get::<_, Option<f64>>(1)?.unwrap_or(0.0),
+                revenue: row.get::<_, Option<f64>>(2)?.unwrap_or(0.0),
+            })
+        })
+        .map_err(|e| AppError::db("Failed to query revenue", e))?;
+
+    rows.collect::<SqlResult<Vec<_>>>()
+        .map_err(|e| AppError::db("Failed to parse revenue row", e))
+}
+
+// Records that the order's invoice was printed or saved as a PDF. Like set_priority this
+// isn't an edit, so it works on locked orders and leaves the version alone; any later
+// edit clears the stamp again.
+#[tauri::command]
+fn mark_printed(order_no: String, pool: tauri::State<DbPool>) -> Result<(), AppError> {
+    ensure_writable()?;
+    let conn = checkout(&pool)?;
+
+    let updated = conn.execute(
+        "UPDATE orders SET printed_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now'), updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') WHERE order_no = ?1",
+        [&order_no],
+    )
+    .map_err(|e| AppError::db("Failed to mark order as printed", e))?;
+
+    if updated == 0 {
+        return Err(AppError::NotFound(format!("Order {} not found", order_no)));
+    }
+
+    Ok(())
+}
+
+// The statuses the order form offers, in workflow order
+const ORDER_STATUSES: [&str; 5] = ["New", "In Progress", "Ready to deliver", "Delivered", "Rejected"];
+
+// Cards per kanban column; Delivered in particular grows without bound
+const KANBAN_COLUMN_LIMIT: u32 = 50;
+
+// Orders (not quotations) grouped by status for the kanban board, without items. Each
+// column is most urgent first, then oldest first, capped at KANBAN_COLUMN_LIMIT. Every
+// standard status has a column even when empty; any other stored status gets its own.
+#[tauri::command]
+fn orders_kanban(pool: tauri::State<DbPool>) -> Result<std::collections::HashMap<String, Vec<Order>>, AppError> {
+    let conn = checkout(&pool)?;
+    let orders = query_orders(
+        &conn,
+        &format!(
+            "SELECT {} FROM (
+                 SELECT *, ROW_NUMBER() OVER (PARTITION BY status ORDER BY priority DESC, date, order_no) AS position
+                 FROM orders
+                 WHERE doc_type = 'order'
+             )
+             WHERE position <= ?1
+             ORDER BY status, position",
+            ORDER_COLUMNS
+        ),
+        [KANBAN_COLUMN_LIMIT],
+        false,
+    )?;
+
+    let mut columns: std::collections::HashMap<String, Vec<Order>> =
+        ORDER_STATUSES.iter().map(|status| (status.to_string(), Vec::new())).collect();
+    for order in orders {
+        columns.entry(order.status.clone()).or_default().push(order);
+    }
+    Ok(columns)
+}
+
+// Restores one order from a JSON backup (an export or import file), overwriting the
+// current copy if there is one. The backed-up amounts are kept as they were rather than
+// recomputed. A locked order must be unlocked first.
+#[tauri::command]
+fn restore_order_from_backup(file_path: String, order_no: String, pool: tauri::State<DbPool>) -> Result<Order, AppError> {
+    use rusqlite::OptionalExtension;
+
+    ensure_writable()?;
+    let order_no = order_no.trim();
+    let record = read_import_records(&file_path)?
+        .into_iter()
+        .find(|record| record.get("orderNo").and_then(|v| v.as_str()).map(str::trim) == Some(order_no))
+        .ok_or_else(|| AppError::NotFound(format!("Order {} is not in the backup file", order_no)))?;
+
+    let mut order: Order = serde_json::from_value(record)
+        .map_err(|e| AppError::Validation(format!("Order {} in the backup can't be read: {}", order_no, e)))?;
+    order.order_no = order_no.to_string();
+    order.validate().map_err(|errors| AppError::Validation(errors.join("; ")))?;
+
+    let mut conn = checkout(&pool)?;
+    let tx = conn.transaction().map_err(|e| AppError::db("Failed to start transaction", e))?;
+    // The backup is deliberately older than what's stored, so it mustn't trip the
+    // concurrent-edit check. Reading the version in the same transaction as the save
+    // means an edit committed in between fails the restore rather than being lost.
+    let stored_version: Option<u32> = tx
+        .query_row("SELECT version FROM orders WHERE order_no = ?1", [order_no], |row| row.get(0))
+        .optional()
+        .map_err(|e| AppError::db("Failed to check order version", e))?;
+    order.version = stored_version.unwrap_or(0);
+
+    save_order_in_tx(&tx, &order)?;
+    record_audit(&tx, order_no, AUDIT_RESTORED, &format!("from {}", file_path))?;
+    tx.commit().map_err(|e| AppError::db("Failed to commit transaction", e))?;
+
+    load_order_by_no(&conn, order_no)?
+        .ok_or_else(|| AppError::Internal(format!("Order {} disappeared after restoring", order_no)))
+}
+
+#[derive(Debug, Serialize)]
+struct TaxBreakdown {
+    #[serde(rename = "orderNo")]
+    order_no: String,
+    #[serde(rename = "taxableValue")]
+    taxable_value: f64,
+    #[serde(rename = "gstRate")]
+    gst_rate: f64,
+    #[serde(rename = "interState")]
+    inter_state: bool,
+    cgst: f64,
+    sgst: f64,
+    igst: f64,
+    // What the stored total differs from taxable value + tax by, from rounding
+    #[serde(rename = "roundingAdjustment")]
+    rounding_adjustment: f64,
+    total: f64,
+}
+
+// An order's tax lines as the invoice shows them: the discounted subtotal, GST split
+// into CGST/SGST or IGST by place of supply (decided as in export_gstr1), and whatever
+// rounding put between their sum and the stored total
+fn tax_breakdown(order: &Order, company: &CompanyProfile) -> TaxBreakdown {
+    let inter_state = gstin_state_code(&order.gstin).is_some_and(|state| state != company_state_code(company));
+    let taxable_value = round2(order.subtotal - order.discount_amount);
+    let (igst, cgst, sgst) = gst_split(order.gst, inter_state);
+
+    TaxBreakdown {
+        order_no: order.order_no.clone(),
+        taxable_value,
+        gst_rate: order.gst_rate,
+        inter_state,
+        cgst,
+        sgst,
+        igst,
+        rounding_adjustment: round2(order.total - taxable_value - igst - cgst - sgst),
+        total: order.total,
+    }
+}
+
 #[tauri::command]
-fn load_orders(page: Option<u32>, pageSize: Option<u32>) -> Result<PaginatedOrders, String> {
-    let page = page.unwrap_or(1);
-    let page_size = pageSize.unwrap_or(50);
-    let orders = load_orders_paginated_from_db(Some(page), Some(page_size))?;
-    let total = get_total_orders_count()?;
-    let total_pages = (total as f64 / page_size as f64).ceil() as u32;
-    
-    Ok(PaginatedOrders {
-        orders,
-        total,
-        page,
-        page_size,
-        total_pages,
+fn order_tax_breakdown(order_no: String, pool: tauri::State<DbPool>) -> Result<TaxBreakdown, AppError> {
+    let conn = checkout(&pool)?;
+    let order = load_order_by_no(&conn, &order_no)?
+        .ok_or_else(|| AppError::NotFound(format!("Order {} not found", order_no)))?;
+    let company = load_company_profile(&conn)?;
+    Ok(tax_breakdown(&order, &company))
+}
+
+#[derive(Debug, Serialize)]
+struct InvoiceData {
+    order: Order,
+    #[serde(rename = "amountInWords")]
+    amount_in_words: String,
+    tax: TaxBreakdown,
+    company: CompanyProfile,
+}
+
+// Everything the invoice screen shows, in one call: the order with its items, the total
+// in words, the tax breakdown and the letterhead
+#[tauri::command]
+fn get_invoice_data(order_no: String, pool: tauri::State<DbPool>) -> Result<InvoiceData, AppError> {
+    let conn = checkout(&pool)?;
+    let order = load_order_by_no(&conn, &order_no)?
+        .ok_or_else(|| AppError::NotFound(format!("Order {} not found", order_no)))?;
+    let company = load_company_profile(&conn)?;
+
+    Ok(InvoiceData {
+        amount_in_words: amount_to_words(order.total)?,
+        tax: tax_breakdown(&order, &company),
+        order,
+        company,
     })
 }
 
+// Backslash-escapes the characters that would break a TSV row (tab, newline, carriage
+// return) and the backslash itself
+fn tsv_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\\' => escaped.push_str("\\\\"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// Tab-separated orders for pasting into Google Sheets, where commas in remarks would
+// break a CSV. Same columns as export_orders, optionally narrowed to an inclusive
+// YYYY-MM-DD date range and/or a status. Amounts are plain numbers with two decimals.
+// Returns the number of orders written.
 #[tauri::command]
-fn save_order(order: serde_json::Value) -> Result<(), String> {
-    let order: Order = serde_json::from_value(order)
-        .map_err(|e| format!("Failed to parse order: {}", e))?;
-    save_order_to_db(&order)
+fn export_orders_tsv(
+    file_path: String,
+    columns: Option<Vec<String>>,
+    from: Option<String>,
+    to: Option<String>,
+    status: Option<String>,
+    pool: tauri::State<DbPool>,
+) -> Result<u32, AppError> {
+    use std::io::Write;
+
+    let columns = resolve_export_columns(columns)?;
+    let conn = checkout(&pool)?;
+    let orders = query_orders(
+        &conn,
+        &format!(
+            "SELECT {} FROM orders
+             WHERE (?1 IS NULL OR date >= ?1)
+               AND (?2 IS NULL OR date <= ?2)
+               AND (?3 IS NULL OR status = ?3)
+             ORDER BY created_date DESC",
+            ORDER_COLUMNS
+        ),
+        rusqlite::params![from, to, status],
+        false,
+    )?;
+
+    let file = std::fs::File::create(&file_path)
+        .map_err(|e| AppError::Internal(format!("Failed to create TSV file: {}", e)))?;
+    let mut writer = std::io::BufWriter::new(file);
+    let write_err = |e: std::io::Error| AppError::Internal(format!("Failed to write TSV file: {}", e));
+
+    let header: Vec<&str> = columns.iter().map(|(_, header)| *header).collect();
+    writeln!(writer, "{}", header.join("\t")).map_err(write_err)?;
+    for order in &orders {
+        let mut fields = Vec::with_capacity(columns.len());
+        for (key, _) in &columns {
+            fields.push(match order_cell(order, key)? {
+                OrderCell::Text(text) => tsv_escape(text),
+                OrderCell::Amount(amount) => format!("{:.2}", amount),
+            });
+        }
+        writeln!(writer, "{}", fields.join("\t")).map_err(write_err)?;
+    }
+    writer.flush().map_err(write_err)?;
+
+    Ok(orders.len() as u32)
+}
+
+#[derive(Debug, Serialize)]
+struct DayCount {
+    date: String,
+    count: u32,
+    total: f64,
 }
 
+// Longest range daily_counts fills in, about ten years of days
+const MAX_DAILY_COUNT_DAYS: i64 = 3660;
+
+// Orders and their total value for every day in an inclusive YYYY-MM-DD range, days
+// without orders included as zeros, for the activity heatmap. Quotations don't count.
 #[tauri::command]
-fn update_order_status(order_no: String, status: String) -> Result<(), String> {
-    let conn = get_connection().map_err(|e| format!("Database error: {}", e))?;
-    
-    conn.execute(
-        "UPDATE orders SET status = ?1 WHERE order_no = ?2",
-        rusqlite::params![status, order_no],
-    )
-    .map_err(|e| format!("Failed to update status: {}", e))?;
+fn daily_counts(from: String, to: String, pool: tauri::State<DbPool>) -> Result<Vec<DayCount>, AppError> {
+    use std::collections::HashMap;
 
-    Ok(())
+    let (start, end) = parse_date_range(&from, &to)?;
+    if (end - start).num_days() >= MAX_DAILY_COUNT_DAYS {
+        return Err(AppError::Validation(format!("Choose a range of at most {} days", MAX_DAILY_COUNT_DAYS)));
+    }
+
+    let conn = checkout(&pool)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT date, COUNT(*), ROUND(COALESCE(SUM(total), 0), 2) FROM orders
+             WHERE date >= ?1 AND date <= ?2 AND doc_type = 'order'
+             GROUP BY date",
+        )
+        .map_err(|e| AppError::db("Failed to prepare daily counts query", e))?;
+    let rows = stmt
+        .query_map(
+            [start.format("%Y-%m-%d").to_string(), end.format("%Y-%m-%d").to_string()],
+            |row| Ok((row.get::<_, String>(0)?, (row.get::<_, u32>(1)?, row.get::<_, f64>(2)?))),
+        )
+        .map_err(|e| AppError::db("Failed to query daily counts", e))?;
+    let by_date: HashMap<String, (u32, f64)> = rows
+        .collect::<SqlResult<_>>()
+        .map_err(|e| AppError::db("Failed to parse daily count", e))?;
+
+    Ok(start
+        .iter_days()
+        .take_while(|day| *day <= end)
+        .map(|day| {
+            let date = day.format("%Y-%m-%d").to_string();
+            let (count, total) = by_date.get(&date).copied().unwrap_or((0, 0.0));
+            DayCount { date, count, total }
+        })
+        .collect())
 }
 
+// Deletes every order matching all the given filters: an exact status, an order date
+// before `before_date` (YYYY-MM-DD), and a customer name (ignoring case and surrounding
+// spaces). At least one filter is required. All or nothing: if any matching order is
+// locked, nothing is deleted. There's no soft delete, but each order gets an audit entry.
 #[tauri::command]
-fn delete_order(order_no: String) -> Result<(), String> {
-    let conn = get_connection().map_err(|e| format!("Database error: {}", e))?;
-    
-    // Delete order (items will be deleted automatically due to CASCADE)
-    conn.execute(
-        "DELETE FROM orders WHERE order_no = ?1",
-        [order_no],
+fn delete_orders_by_filter(status: Option<String>, before_date: Option<String>, customer: Option<String>, pool: tauri::State<DbPool>) -> Result<u32, AppError> {
+    ensure_writable()?;
+    let normalize = |value: Option<String>| value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+    let (status, before_date, customer) = (normalize(status), normalize(before_date), normalize(customer));
+    if status.is_none() && before_date.is_none() && customer.is_none() {
+        return Err(AppError::Validation("Choose at least one filter; use delete_all_orders to delete everything".to_string()));
+    }
+    if let Some(date) = &before_date {
+        chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|_| AppError::Validation(format!("Invalid date '{}' (expected YYYY-MM-DD)", date)))?;
+    }
+
+    const FILTER: &str = "(?1 IS NULL OR status = ?1)
+         AND (?2 IS NULL OR date < ?2)
+         AND (?3 IS NULL OR TRIM(customer_name) = ?3 COLLATE NOCASE)";
+    let params = rusqlite::params![status, before_date, customer];
+
+    let mut conn = checkout(&pool)?;
+    let tx = conn.transaction().map_err(|e| AppError::db("Failed to start transaction", e))?;
+
+    let locked: u32 = tx
+        .query_row(&format!("SELECT COUNT(*) FROM orders WHERE locked = 1 AND {}", FILTER), params, |row| row.get(0))
+        .map_err(|e| AppError::db("Failed to count locked orders", e))?;
+    if locked > 0 {
+        return Err(AppError::Locked(format!("{} matching order(s) are locked; unlock them first", locked)));
+    }
+
+    tx.execute(
+        &format!(
+            "INSERT INTO audit_log (order_no, action, changed_at, details)
+             SELECT order_no, ?4, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'), 'delete by filter' FROM orders WHERE {}",
+            FILTER
+        ),
+        rusqlite::params![status, before_date, customer, AUDIT_DELETED],
+    ).map_err(|e| AppError::db("Failed to record audit entries", e))?;
+
+    // Items and attachments go with their orders via ON DELETE CASCADE
+    let deleted = tx.execute(&format!("DELETE FROM orders WHERE {}", FILTER), params)
+        .map_err(|e| AppError::db("Failed to delete orders", e))?;
+
+    tx.commit().map_err(|e| AppError::db("Failed to commit transaction", e))?;
+    Ok(deleted as u32)
+}
+
+// Lets the order form check a number against the configured pattern before saving
+#[tauri::command]
+fn is_valid_order_number(order_no: String, pool: tauri::State<DbPool>) -> Result<bool, AppError> {
+    let conn = checkout(&pool)?;
+    Ok(match order_no_pattern(&conn)? {
+        Some((_, regex)) => regex.is_match(order_no.trim()),
+        None => true,
+    })
+}
+
+#[derive(Debug, Serialize)]
+struct MachineDay {
+    machine: String,
+    date: String,
+    orders: u32,
+    qty: f64,
+}
+
+// Orders and item quantity per machine per order date, for an inclusive YYYY-MM-DD range.
+// Machines are grouped ignoring case and surrounding spaces, with a blank machine shown as
+// "Unassigned". Quotations and rejected orders are left out.
+#[tauri::command]
+fn machine_throughput(from: String, to: String, pool: tauri::State<DbPool>) -> Result<Vec<MachineDay>, AppError> {
+    let (start, end) = parse_date_range(&from, &to)?;
+    let (from, to) = (start.to_string(), end.to_string());
+
+    let conn = checkout(&pool)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT COALESCE(NULLIF(MIN(TRIM(o.machine_name)), ''), 'Unassigned') AS machine, o.date,
+                    COUNT(DISTINCT o.order_no), COALESCE(SUM(i.qty), 0)
+             FROM orders o
+             LEFT JOIN order_items i ON i.order_no = o.order_no
+             WHERE o.date >= ?1 AND o.date <= ?2 AND o.doc_type = 'order' AND o.status != 'Rejected'
+             GROUP BY LOWER(TRIM(COALESCE(o.machine_name, ''))), o.date
+             ORDER BY machine, o.date",
+        )
+        .map_err(|e| AppError::db("Failed to prepare throughput query", e))?;
+
+    let rows = stmt
+        .query_map([from.trim(), to.trim()], |row| {
+            Ok(MachineDay {
+                machine: row.get(0)?,
+                date: row.get(1)?,
+                orders: row.get(2)?,
+                qty: row.get(3)?,
+            })
+        })
+        .map_err(|e| AppError::db("Failed to query throughput", e))?;
+
+    rows.collect::<SqlResult<Vec<_>>>()
+        .map_err(|e| AppError::db("Failed to parse throughput row", e))
+}
+
+// A scannable QR code for `text` as a base64 PNG, ready for a data: URI
+fn qr_png_base64(text: &str) -> Result<String, AppError> {
+    use base64::Engine;
+
+    let code = qrcode::QrCode::new(text.as_bytes())
+        .map_err(|e| AppError::Internal(format!("Failed to build QR code: {}", e)))?;
+    let image = code.render::<image::Luma<u8>>().min_dimensions(160, 160).build();
+
+    let mut png = std::io::Cursor::new(Vec::new());
+    image::DynamicImage::ImageLuma8(image)
+        .write_to(&mut png, image::ImageFormat::Png)
+        .map_err(|e| AppError::Internal(format!("Failed to encode QR code: {}", e)))?;
+    Ok(base64::engine::general_purpose::STANDARD.encode(png.into_inner()))
+}
+
+// A one-page order summary with a QR code of the order number, for sticking on printed
+// paperwork. The app has no URL scheme to deep-link into, so the code holds just the
+// number, which any phone scanner shows as text and the order search finds directly.
+#[tauri::command]
+fn generate_order_qr_html(order_no: String, pool: tauri::State<DbPool>) -> Result<String, AppError> {
+    let conn = checkout(&pool)?;
+    let order = load_order_by_no(&conn, &order_no)?
+        .ok_or_else(|| AppError::NotFound(format!("Order {} not found", order_no)))?;
+    let company = load_company_profile(&conn)?;
+    let qr = qr_png_base64(&order.order_no)?;
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!("<title>Order {}</title>\n", xml_escape(&order.order_no)));
+    html.push_str(
+        "<style>\n\
+         body { font-family: Arial, sans-serif; font-size: 12px; margin: 24px; }\n\
+         .summary { display: flex; justify-content: space-between; align-items: flex-start; }\n\
+         h1 { margin: 0 0 8px; font-size: 20px; }\n\
+         p { margin: 2px 0; }\n\
+         .qr { text-align: center; }\n\
+         .qr img { width: 160px; height: 160px; image-rendering: pixelated; }\n\
+         </style>\n</head>\n<body>\n",
+    );
+
+    html.push_str("<div class=\"summary\">\n<div>\n");
+    html.push_str(&format!("<h1>{}</h1>\n", xml_escape(&company.name)));
+    html.push_str(&format!("<p><strong>Order No:</strong> {}</p>\n", xml_escape(&order.order_no)));
+    html.push_str(&format!("<p><strong>Date:</strong> {}</p>\n", xml_escape(&order.date)));
+    html.push_str(&format!("<p><strong>Customer:</strong> {}</p>\n", xml_escape(&order.customer_name)));
+    if !order.machine_name.is_empty() {
+        html.push_str(&format!("<p><strong>Machine:</strong> {}</p>\n", xml_escape(&order.machine_name)));
+    }
+    html.push_str(&format!("<p><strong>Status:</strong> {}</p>\n", xml_escape(&order.status)));
+    html.push_str(&format!("<p><strong>Items:</strong> {}</p>\n", order.items.len()));
+    html.push_str(&format!("<p><strong>Total:</strong> Rs. {:.2}</p>\n", order.total));
+    html.push_str("</div>\n<div class=\"qr\">\n");
+    html.push_str(&format!(
+        "<img src=\"data:image/png;base64,{}\" alt=\"QR code for order {}\">\n<p>{}</p>\n",
+        qr,
+        xml_escape(&order.order_no),
+        xml_escape(&order.order_no)
+    ));
+    html.push_str("</div>\n</div>\n</body>\n</html>\n");
+
+    Ok(html)
+}
+
+// Maintenance for when the order list gets slow: puts back the status and date indexes
+// the list view relies on if they've gone missing (e.g. dropped by hand or by an external
+// tool), rebuilds every index and refreshes the query planner's statistics
+#[tauri::command]
+fn reindex_database(pool: tauri::State<DbPool>) -> Result<(), AppError> {
+    ensure_writable()?;
+    let conn = checkout(&pool)?;
+    conn.execute_batch(
+        "CREATE INDEX IF NOT EXISTS idx_order_status ON orders(status);
+         CREATE INDEX IF NOT EXISTS idx_order_date ON orders(date);
+         REINDEX;
+         ANALYZE;",
     )
-    .map_err(|e| format!("Failed to delete order: {}", e))?;
+    .map_err(|e| AppError::db("Failed to reindex database", e))
+}
 
-    Ok(())
+#[derive(Debug, Serialize)]
+struct FlatRow {
+    #[serde(flatten)]
+    item: OrderItem,
+    #[serde(rename = "orderNo")]
+    order_no: String,
+    date: String,
+    #[serde(rename = "customerName")]
+    customer_name: String,
+    #[serde(rename = "contactPerson")]
+    contact_person: String,
+    phone: String,
+    gstin: String,
+    status: String,
+    #[serde(rename = "docType")]
+    doc_type: String,
+    #[serde(rename = "machineName")]
+    machine_name: String,
+    #[serde(rename = "buyerOrderNo")]
+    buyer_order_no: String,
+    #[serde(rename = "gstRate")]
+    gst_rate: f64,
+    #[serde(rename = "orderTotal")]
+    order_total: f64,
 }
 
+// One row per item with its order's header fields alongside, for spreadsheets and BI
+// tools that want a flat table. Optionally limited to an inclusive YYYY-MM-DD range of
+// order dates; quotations are included and marked by docType.
 #[tauri::command]
-fn export_orders(file_path: String) -> Result<(), String> {
-    // Load all orders for export (no pagination)
-    let orders = load_orders_from_db()?;
-    
-    // Use rust_xlsxwriter to create Excel file
-    use rust_xlsxwriter::*;
-    
-    let mut workbook = Workbook::new();
+fn load_flat_rows(from: Option<String>, to: Option<String>, pool: tauri::State<DbPool>) -> Result<Vec<FlatRow>, AppError> {
+    let conn = checkout(&pool)?;
+    let columns = prefixed_item_columns("i");
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {}, o.order_no, o.date, o.customer_name, o.contact_person, o.phone, o.gstin, o.status,
+                    o.doc_type, o.machine_name, o.buyer_order_no, o.gst_rate, o.total
+             FROM order_items i
+             JOIN orders o ON o.order_no = i.order_no
+             WHERE (?1 IS NULL OR o.date >= ?1)
+               AND (?2 IS NULL OR o.date <= ?2)
+             ORDER BY o.date, o.order_no, i.sl_no",
+            columns
+        ))
+        .map_err(|e| AppError::db("Failed to prepare flat rows query", e))?;
+
+    let rows = stmt
+        .query_map(rusqlite::params![from, to], |row| {
+            Ok(FlatRow {
+                item: item_from_row(row)?,
+                order_no: row.get(10)?,
+                date: row.get(11)?,
+                customer_name: row.get(12)?,
+                contact_person: row.get::<_, Option<String>>(13)?.unwrap_or_default(),
+                phone: row.get::<_, Option<String>>(14)?.unwrap_or_default(),
+                gstin: row.get::<_, Option<String>>(15)?.unwrap_or_default(),
+                status: row.get(16)?,
+                doc_type: row.get(17)?,
+                machine_name: row.get::<_, Option<String>>(18)?.unwrap_or_default(),
+                buyer_order_no: row.get::<_, Option<String>>(19)?.unwrap_or_default(),
+                gst_rate: row.get::<_, Option<f64>>(20)?.unwrap_or_default(),
+                order_total: row.get(21)?,
+            })
+        })
+        .map_err(|e| AppError::db("Failed to query flat rows", e))?;
+
+    rows.collect::<SqlResult<Vec<_>>>()
+        .map_err(|e| AppError::db("Failed to parse flat row", e))
+}
+
+// Open orders due within the next `days` days (0 = due today), local date, soonest first.
+// Overdue orders are included too, since they need chasing the most. Delivered and
+// rejected orders, quotations and orders without a due date are left out.
+#[tauri::command]
+fn orders_due_soon(days: u32, pool: tauri::State<DbPool>) -> Result<Vec<Order>, AppError> {
+    let conn = checkout(&pool)?;
+    query_orders(
+        &conn,
+        &format!(
+            "SELECT {} FROM orders
+             WHERE due_date IS NOT NULL
+               AND due_date <= date('now', 'localtime', '+' || ?1 || ' days')
+               AND doc_type = 'order'
+               AND status NOT IN (?2, 'Rejected')
+             ORDER BY due_date, priority DESC, order_no
+             LIMIT ?3",
+            ORDER_COLUMNS
+        ),
+        rusqlite::params![days, STATUS_DELIVERED, MAX_PAGE_SIZE],
+        false,
+    )
+}
+
+// Monthly sales register (month as YYYY-MM): one row per invoice with its taxable value,
+// CGST, SGST, IGST and total from tax_breakdown, then a bold totals row. Quotations and
+// rejected orders are excluded as in export_gstr1. Returns the number of invoices listed.
+#[tauri::command]
+fn export_sales_register(month: String, file_path: String, pool: tauri::State<DbPool>) -> Result<u32, AppError> {
+    let period = chrono::NaiveDate::parse_from_str(&format!("{}-01", month.trim()), "%Y-%m-%d")
+        .map_err(|_| AppError::Validation(format!("Invalid month '{}' (expected YYYY-MM)", month)))?;
+
+    let conn = checkout(&pool)?;
+    let company = load_company_profile(&conn)?;
+    let orders = query_orders(
+        &conn,
+        &format!(
+            "SELECT {} FROM orders
+             WHERE substr(date, 1, 7) = ?1 AND doc_type = 'order' AND status != 'Rejected'
+             ORDER BY date, order_no",
+            ORDER_COLUMNS
+        ),
+        [period.format("%Y-%m").to_string()],
+        false,
+    )?;
+
+    let mut workbook = rust_xlsxwriter::Workbook::new();
     let worksheet = workbook.add_worksheet();
+    let header_format = excel_header_format();
+    let currency_format = excel_currency_format();
+    let total_format = excel_currency_format().set_bold();
 
-    // Write headers
-    let headers = vec![
-        "Order No", "Date", "Customer Name", "Contact Person", "Phone",
-        "Status", "Machine Name", "Subtotal", "GST", "Total", "Remarks",
-        "Delivery Note", "Delivery Note Date", "Buyer's Order Number", "Buyer's Order Date", "Created Date"
-    ];
-    
+    let headers = ["Invoice No", "Date", "Customer", "GSTIN", "Taxable Value", "CGST", "SGST", "IGST", "Total"];
     for (col, header) in headers.iter().enumerate() {
-        let _ = worksheet.write_string(0, col as u16, *header)
-            .map_err(|e| format!("Failed to write header: {}", e))?;
+        worksheet.write_string_with_format(0, col as u16, *header, &header_format)
+            .map_err(|e| AppError::Internal(format!("Failed to write header: {}", e)))?;
     }
 
-    // Write orders
-    for (row, order) in orders.iter().enumerate() {
-        let row_num = (row + 1) as u32;
-        worksheet.write_string(row_num, 0, &order.order_no)
-            .map_err(|e| format!("Failed to write orderNo: {}", e))?;
-        worksheet.write_string(row_num, 1, &order.date)
-            .map_err(|e| format!("Failed to write date: {}", e))?;
-        worksheet.write_string(row_num, 2, &order.customer_name)
-            .map_err(|e| format!("Failed to write customerName: {}", e))?;
-        worksheet.write_string(row_num, 3, &order.contact_person)
-            .map_err(|e| format!("Failed to write contactPerson: {}", e))?;
-        worksheet.write_string(row_num, 4, &order.phone)
-            .map_err(|e| format!("Failed to write phone: {}", e))?;
-        worksheet.write_string(row_num, 5, &order.status)
-            .map_err(|e| format!("Failed to write status: {}", e))?;
-        worksheet.write_string(row_num, 6, &order.machine_name)
-            .map_err(|e| format!("Failed to write machineName: {}", e))?;
-        worksheet.write_number(row_num, 7, order.subtotal)
-            .map_err(|e| format!("Failed to write subtotal: {}", e))?;
-        worksheet.write_number(row_num, 8, order.gst)
-            .map_err(|e| format!("Failed to write gst: {}", e))?;
-        worksheet.write_number(row_num, 9, order.total)
-            .map_err(|e| format!("Failed to write total: {}", e))?;
-        worksheet.write_string(row_num, 10, &order.remarks)
-            .map_err(|e| format!("Failed to write remarks: {}", e))?;
-        worksheet.write_string(row_num, 11, &order.delivery_note)
-            .map_err(|e| format!("Failed to write deliveryNote: {}", e))?;
-        worksheet.write_string(row_num, 12, &order.delivery_note_date)
-            .map_err(|e| format!("Failed to write deliveryNoteDate: {}", e))?;
-        worksheet.write_string(row_num, 13, &order.buyer_order_no)
-            .map_err(|e| format!("Failed to write buyerOrderNo: {}", e))?;
-        worksheet.write_string(row_num, 14, &order.buyer_order_date)
-            .map_err(|e| format!("Failed to write buyerOrderDate: {}", e))?;
-        worksheet.write_string(row_num, 15, &order.created_date)
-            .map_err(|e| format!("Failed to write createdDate: {}", e))?;
+    // Taxable value, CGST, SGST, IGST, total
+    let mut totals = [0.0; 5];
+    let mut row_num = 1u32;
+    for order in &orders {
+        let tax = tax_breakdown(order, &company);
+        for (col, text) in [&order.order_no, &order.date, &order.customer_name, &order.gstin].iter().enumerate() {
+            worksheet.write_string(row_num, col as u16, *text)
+                .map_err(|e| AppError::Internal(format!("Failed to write invoice: {}", e)))?;
+        }
+        let amounts = [tax.taxable_value, tax.cgst, tax.sgst, tax.igst, tax.total];
+        for (i, amount) in amounts.iter().enumerate() {
+            totals[i] += amount;
+            worksheet.write_number_with_format(row_num, (i + 4) as u16, *amount, &currency_format)
+                .map_err(|e| AppError::Internal(format!("Failed to write amount: {}", e)))?;
+        }
+        row_num += 1;
+    }
+
+    worksheet.write_string_with_format(row_num, 0, "Total", &header_format)
+        .map_err(|e| AppError::Internal(format!("Failed to write total: {}", e)))?;
+    for (i, amount) in totals.iter().enumerate() {
+        worksheet.write_number_with_format(row_num, (i + 4) as u16, round2(*amount), &total_format)
+            .map_err(|e| AppError::Internal(format!("Failed to write total: {}", e)))?;
     }
 
+    worksheet.autofit();
     workbook.save(&file_path)
-        .map_err(|e| format!("Failed to save Excel file: {}", e))?;
-    
-    Ok(())
+        .map_err(|e| AppError::Internal(format!("Failed to save Excel file: {}", e)))?;
+
+    Ok(orders.len() as u32)
 }
 
+// Most numbers find_number_gaps will list, so a stray typo like ARW-90000 can't produce
+// a list of tens of thousands
+const MAX_NUMBER_GAPS: u64 = 10_000;
+
+// Missing order numbers between the lowest and highest that start with `prefix`, e.g.
+// ARW-0004 when ARW-0003 and ARW-0005 exist. The rest of the number after the prefix
+// (and an optional -, / or _ separator) must be all digits; others are ignored. Missing
+// numbers are written with the same separator and zero padding as the existing ones.
+// Orders moved out by archive_orders_before show up as gaps too.
 #[tauri::command]
-fn save_order_html(file_path: String, content: String) -> Result<(), String> {
-    std::fs::write(&file_path, content)
-        .map_err(|e| format!("Failed to save HTML file: {}", e))?;
-    Ok(())
+fn find_number_gaps(prefix: String, pool: tauri::State<DbPool>) -> Result<Vec<String>, AppError> {
+    let prefix = prefix.trim();
+    if prefix.is_empty() {
+        return Err(AppError::Validation("An order number prefix is required".to_string()));
+    }
+
+    let conn = checkout(&pool)?;
+    let mut stmt = conn
+        .prepare("SELECT order_no FROM orders WHERE substr(order_no, 1, length(?1)) = ?1")
+        .map_err(|e| AppError::db("Failed to prepare order number query", e))?;
+    let order_nos = stmt
+        .query_map([prefix], |row| row.get::<_, String>(0))
+        .map_err(|e| AppError::db("Failed to query order numbers", e))?
+        .collect::<SqlResult<Vec<_>>>()
+        .map_err(|e| AppError::db("Failed to parse order number", e))?;
+
+    number_gaps(prefix, &order_nos)
 }
 
-fn main() {
-    // Initialize database on startup
-    if let Err(e) = init_database() {
-        eprintln!("Warning: Failed to initialize database: {}", e);
+// The gap search behind find_number_gaps, over order numbers that all start with `prefix`
+fn number_gaps(prefix: &str, order_nos: &[String]) -> Result<Vec<String>, AppError> {
+    use std::collections::BTreeSet;
+
+    let mut numbers = BTreeSet::new();
+    let mut separator = String::new();
+    let mut width = 0;
+    for order_no in order_nos {
+        let Some(rest) = order_no.strip_prefix(prefix) else {
+            continue;
+        };
+        let digits = rest.trim_start_matches(['-', '/', '_']);
+        if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+            continue;
+        }
+        let Ok(number) = digits.parse::<u64>() else {
+            continue;
+        };
+        if numbers.is_empty() {
+            separator = rest[..rest.len() - digits.len()].to_string();
+        }
+        if digits.starts_with('0') {
+            width = width.max(digits.len());
+        }
+        numbers.insert(number);
+    }
+
+    let (Some(&first), Some(&last)) = (numbers.first(), numbers.last()) else {
+        return Ok(Vec::new());
+    };
+    let missing = (last - first + 1) - numbers.len() as u64;
+    if missing > MAX_NUMBER_GAPS {
+        return Err(AppError::Validation(format!(
+            "{} numbers are missing between {} and {}; check for a mistyped order number",
+            missing, first, last
+        )));
+    }
+
+    Ok((first..=last)
+        .filter(|n| !numbers.contains(n))
+        .map(|n| format!("{}{}{:0width$}", prefix, separator, n, width = width))
+        .collect())
+}
+
+#[derive(Debug, Serialize)]
+struct SelectedExport {
+    exported: u32,
+    // Requested numbers with no matching order, which were skipped
+    missing: Vec<String>,
+}
+
+// The orders picked in the UI, in the order given, to one sheet with the standard
+// columns. Numbers listed twice are exported once.
+#[tauri::command]
+fn export_orders_selected(order_nos: Vec<String>, file_path: String, pool: tauri::State<DbPool>) -> Result<SelectedExport, AppError> {
+    if order_nos.is_empty() {
+        return Err(AppError::Validation("Select at least one order to export".to_string()));
+    }
+
+    let conn = checkout(&pool)?;
+    let mut seen = std::collections::HashSet::new();
+    let mut orders = Vec::new();
+    let mut missing = Vec::new();
+    for order_no in &order_nos {
+        let order_no = order_no.trim();
+        if !seen.insert(order_no) {
+            continue;
+        }
+        match load_order_by_no(&conn, order_no)? {
+            Some(order) => orders.push(order),
+            None => missing.push(order_no.to_string()),
+        }
+    }
+    if orders.is_empty() {
+        return Err(AppError::NotFound("None of the selected orders exist".to_string()));
+    }
+
+    let mut workbook = rust_xlsxwriter::Workbook::new();
+    let worksheet = workbook.add_worksheet();
+    write_orders_sheet(worksheet, &orders)?;
+    workbook.save(&file_path)
+        .map_err(|e| AppError::Internal(format!("Failed to save Excel file: {}", e)))?;
+
+    Ok(SelectedExport {
+        exported: orders.len() as u32,
+        missing,
+    })
+}
+
+#[derive(Debug, Serialize)]
+struct CustomerScore {
+    #[serde(rename = "customerName")]
+    customer_name: String,
+    orders: u32,
+    #[serde(rename = "paidOrders")]
+    paid_orders: u32,
+    // Share of orders fully paid, 0..1
+    #[serde(rename = "paidFraction")]
+    paid_fraction: f64,
+    // Days from order date to the payment that cleared it, averaged over paid orders;
+    // None when nothing has been fully paid yet
+    #[serde(rename = "avgDaysToPay")]
+    avg_days_to_pay: Option<f64>,
+}
+
+// Payment track record per customer (names grouped ignoring case and surrounding
+// spaces) from the payments table, least reliable first: lowest paid fraction, then
+// slowest to pay. Quotations, rejected and zero-value orders don't count.
+#[tauri::command]
+fn customer_reliability(pool: tauri::State<DbPool>) -> Result<Vec<CustomerScore>, AppError> {
+    let conn = checkout(&pool)?;
+    let mut stmt = conn
+        .prepare(
+            "WITH running AS (
+                 SELECT order_no, paid_on,
+                        SUM(amount) OVER (PARTITION BY order_no ORDER BY paid_on, id) AS paid_so_far
+                 FROM payments
+             ),
+             settled AS (
+                 SELECT r.order_no, MIN(r.paid_on) AS settled_on
+                 FROM running r
+                 JOIN orders o ON o.order_no = r.order_no
+                 WHERE r.paid_so_far + 0.005 >= o.total
+                 GROUP BY r.order_no
+             )
+             SELECT MIN(TRIM(o.customer_name)), COUNT(*), COUNT(s.settled_on),
+                    ROUND(AVG(MAX(julianday(s.settled_on) - julianday(o.date), 0)), 1)
+             FROM orders o
+             LEFT JOIN settled s ON s.order_no = o.order_no
+             WHERE o.doc_type = 'order' AND o.status != 'Rejected' AND o.total > 0
+             GROUP BY LOWER(TRIM(o.customer_name))",
+        )
+        .map_err(|e| AppError::db("Failed to prepare reliability query", e))?;
+
+    let rows = stmt
+        .query_map([], |row| {
+            let orders: u32 = row.get(1)?;
+            let paid_orders: u32 = row.get(2)?;
+            Ok(CustomerScore {
+                customer_name: row.get(0)?,
+                orders,
+                paid_orders,
+                paid_fraction: (paid_orders as f64 / orders as f64 * 1000.0).round() / 1000.0,
+                avg_days_to_pay: row.get(3)?,
+            })
+        })
+        .map_err(|e| AppError::db("Failed to query reliability", e))?;
+    let mut scores = rows
+        .collect::<SqlResult<Vec<_>>>()
+        .map_err(|e| AppError::db("Failed to parse reliability row", e))?;
+
+    scores.sort_by(|a, b| {
+        a.paid_fraction
+            .total_cmp(&b.paid_fraction)
+            .then(b.avg_days_to_pay.unwrap_or(0.0).total_cmp(&a.avg_days_to_pay.unwrap_or(0.0)))
+            .then_with(|| a.customer_name.cmp(&b.customer_name))
+    });
+    Ok(scores)
+}
+
+// Matches a status against ORDER_STATUSES ignoring case and returns its standard spelling
+fn canonical_status(status: &str) -> Result<&'static str, AppError> {
+    ORDER_STATUSES
+        .iter()
+        .find(|known| known.eq_ignore_ascii_case(status.trim()))
+        .copied()
+        .ok_or_else(|| AppError::Validation(format!(
+            "Unknown status '{}' (expected one of: {})",
+            status,
+            ORDER_STATUSES.join(", ")
+        )))
+}
+
+// Moves every one of a customer's orders in `from_status` to `to_status` in one
+// transaction, e.g. New -> Rejected when they cancel. The customer is matched ignoring
+// case and surrounding spaces. Locked orders are skipped, so the count returned is what
+// actually changed.
+#[tauri::command]
+fn update_customer_orders_status(customer_name: String, from_status: String, to_status: String, pool: tauri::State<DbPool>) -> Result<u32, AppError> {
+    ensure_writable()?;
+    let customer_name = customer_name.trim();
+    if customer_name.is_empty() {
+        return Err(AppError::Validation("Customer name is required".to_string()));
+    }
+    let (from_status, to_status) = (canonical_status(&from_status)?, canonical_status(&to_status)?);
+    if from_status == to_status {
+        return Err(AppError::Validation(format!("Orders are already {}", to_status)));
+    }
+
+    let mut conn = checkout(&pool)?;
+    let tx = conn.transaction().map_err(|e| AppError::db("Failed to start transaction", e))?;
+
+    let order_nos: Vec<String> = {
+        let mut stmt = tx
+            .prepare("SELECT order_no FROM orders WHERE TRIM(customer_name) = ?1 COLLATE NOCASE AND status = ?2 AND locked = 0")
+            .map_err(|e| AppError::db("Failed to prepare order query", e))?;
+        let rows = stmt
+            .query_map([customer_name, from_status], |row| row.get(0))
+            .map_err(|e| AppError::db("Failed to query orders", e))?;
+        rows.collect::<SqlResult<_>>()
+            .map_err(|e| AppError::db("Failed to parse order number", e))?
+    };
+
+    for order_no in &order_nos {
+        tx.execute(
+            "UPDATE orders SET status = ?1, updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now'), version = version + 1 WHERE order_no = ?2",
+            [to_status, order_no.as_str()],
+        )
+        .map_err(|e| AppError::db("Failed to update status", e))?;
+        record_status_change(&tx, order_no, from_status, to_status)?;
+    }
+
+    tx.commit().map_err(|e| AppError::db("Failed to commit transaction", e))?;
+    Ok(order_nos.len() as u32)
+}
+
+// One invoice covering several orders for the same customer (same name ignoring case and
+// spaces, and same GSTIN). Items are renumbered in sequence with each description
+// prefixed by its source order number; the money fields are the orders' own stored
+// figures added up, so each order's discount and rounding carry over unchanged. The
+// combined order isn't saved; its number is the source numbers joined with " + ".
+#[tauri::command]
+fn consolidated_invoice_data(order_nos: Vec<String>, pool: tauri::State<DbPool>) -> Result<InvoiceData, AppError> {
+    let mut seen = std::collections::HashSet::new();
+    let order_nos: Vec<&str> = order_nos.iter().map(|no| no.trim()).filter(|no| seen.insert(*no)).collect();
+    if order_nos.is_empty() {
+        return Err(AppError::Validation("Select at least one order to invoice".to_string()));
+    }
+
+    let conn = checkout(&pool)?;
+    let mut orders = Vec::new();
+    for order_no in &order_nos {
+        let order = load_order_by_no(&conn, order_no)?
+            .ok_or_else(|| AppError::NotFound(format!("Order {} not found", order_no)))?;
+        if order.doc_type == DOC_TYPE_QUOTATION {
+            return Err(AppError::Validation(format!("{} is a quotation; convert it to an order first", order.order_no)));
+        }
+        orders.push(order);
+    }
+
+    let first = &orders[0];
+    let customer_key = first.customer_name.trim().to_lowercase();
+    if let Some(other) = orders.iter().find(|o| o.customer_name.trim().to_lowercase() != customer_key || o.gstin != first.gstin) {
+        return Err(AppError::Validation(format!(
+            "{} is for {} but {} is for {}; a consolidated invoice needs a single customer",
+            other.order_no, other.customer_name, first.order_no, first.customer_name
+        )));
+    }
+
+    let mut combined = first.clone();
+    combined.order_no = order_nos.join(" + ");
+    combined.date = orders.iter().map(|o| o.date.clone()).max().unwrap_or_default();
+    combined.items = orders
+        .iter()
+        .flat_map(|order| {
+            order.items.iter().map(move |item| OrderItem {
+                item_type: format!("{}: {}", order.order_no, item.item_type),
+                ..item.clone()
+            })
+        })
+        .collect();
+    for (index, item) in combined.items.iter_mut().enumerate() {
+        item.sl_no = (index + 1) as u32;
+    }
+    combined.subtotal = round2(orders.iter().map(|o| o.subtotal).sum());
+    combined.discount_amount = round2(orders.iter().map(|o| o.discount_amount).sum());
+    combined.gst = round2(orders.iter().map(|o| o.gst).sum());
+    combined.total = round2(orders.iter().map(|o| o.total).sum());
+    combined.amount_paid = round2(orders.iter().map(|o| o.amount_paid).sum());
+    // A single rate only means something if every order was charged it
+    if orders.iter().any(|o| o.gst_rate != first.gst_rate) {
+        combined.gst_rate = 0.0;
+    }
+    // Fields that belong to a single order only carry over when just one order has them
+    let with_po: Vec<&Order> = orders.iter().filter(|o| !o.buyer_order_no.trim().is_empty()).collect();
+    combined.buyer_order_no = with_po.iter().map(|o| o.buyer_order_no.trim()).collect::<Vec<_>>().join(", ");
+    combined.buyer_order_date = match with_po.as_slice() {
+        [only] => only.buyer_order_date.clone(),
+        _ => String::new(),
+    };
+    if orders.len() > 1 {
+        combined.delivery_note.clear();
+        combined.delivery_note_date.clear();
+        combined.remarks.clear();
     }
 
+    let company = load_company_profile(&conn)?;
+    Ok(InvoiceData {
+        amount_in_words: amount_to_words(combined.total)?,
+        tax: tax_breakdown(&combined, &company),
+        order: combined,
+        company,
+    })
+}
+
+fn main() {
     tauri::Builder::default()
+        .manage(init_pool(&get_db_path()))
+        .manage(ExportCancel::default())
         .invoke_handler(tauri::generate_handler![
             load_orders,
+            recent_orders,
+            load_order_summaries,
             save_order,
             update_order_status,
             delete_order,
             export_orders,
-            save_order_html
+            cancel_export,
+            export_orders_by_status_sheets,
+            export_orders_since,
+            export_order_json,
+            save_order_html,
+            orders_by_month,
+            add_attachment,
+            list_attachments,
+            remove_attachment,
+            next_item_sl_no,
+            validate_import,
+            orders_for_customer,
+            amount_in_words,
+            create_empty_database,
+            load_order_items,
+            delete_all_orders,
+            summary_report,
+            export_summary_pdf,
+            convert_quotation,
+            get_rounding_mode,
+            set_rounding_mode,
+            get_setting,
+            set_setting,
+            get_gst_rate,
+            get_order_prefix,
+            get_company_profile,
+            set_company_profile,
+            find_items,
+            audit_amounts,
+            repair_amounts,
+            outstanding_orders,
+            add_payment,
+            list_payments,
+            remove_payment,
+            top_orders,
+            export_tally_xml,
+            orders_modified_today,
+            gst_summary,
+            gst_liability,
+            renumber_items,
+            recompute_order_totals,
+            export_invoices_zip,
+            get_app_info,
+            find_duplicate_orders,
+            export_all_items,
+            reassign_machine,
+            list_machines,
+            export_orders_ndjson,
+            orders_without_items,
+            update_rates_for_type,
+            generate_challan_html,
+            period_comparison,
+            export_pivot,
+            lock_order,
+            unlock_order,
+            export_audit_log,
+            find_by_buyer_order,
+            avg_order_value_by_month,
+            search_customers_fuzzy,
+            status_duration_report,
+            validate_all_orders,
+            rename_order,
+            load_pending_items,
+            set_priority,
+            checkpoint_wal,
+            backup_database,
+            verify_order_hash,
+            oldest_pending,
+            changes_since,
+            merge_database,
+            export_gstr1,
+            db_health_check,
+            archive_orders_before,
+            export_subset_db,
+            search_orders,
+            search_archive,
+            revenue_by_item_type,
+            mark_printed,
+            orders_kanban,
+            restore_order_from_backup,
+            order_tax_breakdown,
+            get_invoice_data,
+            export_orders_tsv,
+            daily_counts,
+            delete_orders_by_filter,
+            is_valid_order_number,
+            machine_throughput,
+            generate_order_qr_html,
+            reindex_database,
+            load_flat_rows,
+            orders_due_soon,
+            export_sales_register,
+            find_number_gaps,
+            export_orders_selected,
+            customer_reliability,
+            update_customer_orders_status,
+            consolidated_invoice_data
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gstin_with_correct_check_character_is_valid() {
+        assert_eq!(validate_gstin("27AAPFU0939F1ZV"), Ok(()));
+    }
+
+    #[test]
+    fn gstin_with_wrong_check_character_is_rejected() {
+        let err = validate_gstin("27AAPFU0939F1ZW").unwrap_err();
+        assert!(err.contains("invalid check character"), "{}", err);
+    }
+
+    #[test]
+    fn zero_amount_in_words() {
+        assert_eq!(amount_to_words(0.0).unwrap(), "Rupees Zero Only");
+    }
+
+    #[test]
+    fn lakh_and_crore_boundaries_in_words() {
+        assert_eq!(indian_number_words(99_999), "Ninety Nine Thousand Nine Hundred Ninety Nine");
+        assert_eq!(indian_number_words(1_00_000), "One Lakh");
+        assert_eq!(indian_number_words(99_99_999), "Ninety Nine Lakh Ninety Nine Thousand Nine Hundred Ninety Nine");
+        assert_eq!(indian_number_words(1_00_00_000), "One Crore");
+        assert_eq!(indian_number_words(1_01_00_001), "One Crore One Lakh One");
+        assert_eq!(indian_number_words(100_00_00_000), "One Hundred Crore");
+    }
+
+    #[test]
+    fn paise_are_rounded_to_the_nearest_paisa() {
+        assert_eq!(amount_to_words(1200.5).unwrap(), "Rupees One Thousand Two Hundred and Fifty Paise Only");
+        assert_eq!(amount_to_words(10.999).unwrap(), "Rupees Eleven Only");
+        assert_eq!(amount_to_words(0.07).unwrap(), "Rupees Zero and Seven Paise Only");
+    }
+
+    #[test]
+    fn half_up_rounds_ties_away_from_zero() {
+        assert_eq!(RoundingMode::HalfUp.round(2.665), 2.67);
+        assert_eq!(RoundingMode::HalfUp.round(2.675), 2.68);
+        assert_eq!(RoundingMode::HalfUp.round(0.125), 0.13);
+    }
+
+    #[test]
+    fn half_even_rounds_ties_to_the_even_paisa() {
+        assert_eq!(RoundingMode::HalfEven.round(2.665), 2.66);
+        assert_eq!(RoundingMode::HalfEven.round(2.675), 2.68);
+        assert_eq!(RoundingMode::HalfEven.round(0.125), 0.12);
+    }
+
+    #[test]
+    fn rounding_modes_agree_away_from_ties() {
+        for mode in [RoundingMode::HalfUp, RoundingMode::HalfEven] {
+            assert_eq!(mode.round(2.664), 2.66);
+            assert_eq!(mode.round(2.666), 2.67);
+        }
+        assert_eq!(RoundingMode::None.round(2.665), 2.665);
+    }
+
+    fn order_nos(numbers: &[&str]) -> Vec<String> {
+        numbers.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn gaps_keep_the_separator_and_zero_padding() {
+        let gaps = number_gaps("ARW", &order_nos(&["ARW-0001", "ARW-0003", "ARW-0006", "ARW-NOTE"])).unwrap();
+        assert_eq!(gaps, ["ARW-0002", "ARW-0004", "ARW-0005"]);
+    }
+
+    #[test]
+    fn unpadded_numbers_give_unpadded_gaps() {
+        assert_eq!(number_gaps("Q/", &order_nos(&["Q/8", "Q/11"])).unwrap(), ["Q/9", "Q/10"]);
+    }
+
+    #[test]
+    fn too_many_gaps_are_rejected() {
+        let at_limit = format!("ARW-{}", MAX_NUMBER_GAPS + 2);
+        assert_eq!(number_gaps("ARW", &order_nos(&["ARW-1", &at_limit])).unwrap().len() as u64, MAX_NUMBER_GAPS);
+
+        let past_limit = format!("ARW-{}", MAX_NUMBER_GAPS + 3);
+        let err = number_gaps("ARW", &order_nos(&["ARW-1", &past_limit])).unwrap_err();
+        assert!(matches!(err, AppError::Validation(_)), "{}", err);
+    }
+}
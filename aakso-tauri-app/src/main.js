@@ -1,5 +1,5 @@
 // Tauri API imports - will work in Tauri, fail gracefully in browser
-let invoke, saveDialog;
+let invoke, saveDialog, listen;
 
 // Check if Tauri is available
 function checkTauri() {
@@ -13,8 +13,10 @@ async function initTauri() {
         try {
             const { invoke: invokeFn } = await import('@tauri-apps/api/tauri');
             const { save: saveFn } = await import('@tauri-apps/api/dialog');
+            const { listen: listenFn } = await import('@tauri-apps/api/event');
             invoke = invokeFn;
             saveDialog = saveFn;
+            listen = listenFn;
             return true;
         } catch (error) {
             console.error('Failed to load Tauri APIs:', error);
@@ -65,6 +67,8 @@ function setupBrowserMode() {
 
 // State
 let items = [];
+let defaultGstPercent = 18;
+let orderNoPrefix = 'ORD';
 let orders = [];
 let filteredOrders = [];
 let currentOrderIndex = -1;
@@ -84,15 +88,29 @@ document.addEventListener('DOMContentLoaded', () => {
     initializeApp();
 });
 
+// Surfaces an unwritable or unusable database at startup instead of on the first save
+async function checkDatabaseHealth() {
+    try {
+        await invoke('db_health_check');
+    } catch (error) {
+        alert(`Database problem: ${error.message || error}`);
+    }
+}
+
 async function initializeApp() {
     // Initialize Tauri APIs
     const tauriAvailable = await initTauri();
     if (!tauriAvailable) {
         setupBrowserMode();
+    } else {
+        await checkDatabaseHealth();
     }
     
     // Load and apply saved theme
     loadTheme();
+
+    await loadSettings();
+    loadMachineNames();
     
     // Theme toggle handler
     document.getElementById('theme-toggle').addEventListener('click', toggleTheme);
@@ -144,9 +162,11 @@ async function initializeApp() {
     
     document.getElementById('clear-form-btn').addEventListener('click', clearForm);
     document.getElementById('gst-percent').addEventListener('input', updateTotals);
+    document.getElementById('discount-type').addEventListener('change', updateTotals);
+    document.getElementById('discount-value').addEventListener('input', updateTotals);
     
     // Prevent form submission when pressing Enter in item fields
-    document.querySelectorAll('#item-type, #item-qty, #item-length, #item-dia, #item-shore, #item-remarks, #item-rate').forEach(input => {
+    document.querySelectorAll('#item-type, #item-hsn, #item-qty, #item-length, #item-dia, #item-shore, #item-remarks, #item-rate').forEach(input => {
         input.addEventListener('keypress', (e) => {
             if (e.key === 'Enter') {
                 e.preventDefault();
@@ -251,6 +271,7 @@ function addItem() {
     const item = {
         slNo: editingItemIndex !== null ? items[editingItemIndex].slNo : items.length + 1,
         type: document.getElementById('item-type').value.trim(),
+        hsn: document.getElementById('item-hsn').value.trim(),
         qty: qty,
         length: document.getElementById('item-length').value.trim(),
         dia: document.getElementById('item-dia').value.trim(),
@@ -285,6 +306,7 @@ function editItem(index) {
     
     // Populate form fields
     document.getElementById('item-type').value = item.type || '';
+    document.getElementById('item-hsn').value = item.hsn || '';
     document.getElementById('item-qty').value = item.qty === 0 ? '' : item.qty;
     document.getElementById('item-length').value = item.length || '';
     document.getElementById('item-dia').value = item.dia || '';
@@ -337,6 +359,7 @@ function renderItems() {
         }
         row.innerHTML = `
             <td>${item.type || ''}</td>
+            <td>${item.hsn || ''}</td>
             <td>${item.qty === 0 ? '' : item.qty}</td>
             <td>${item.length || ''}</td>
             <td>${item.dia || ''}</td>
@@ -358,6 +381,7 @@ function renderItems() {
 
 function clearItemForm() {
     document.getElementById('item-type').value = '';
+    document.getElementById('item-hsn').value = '';
     document.getElementById('item-qty').value = '';
     document.getElementById('item-length').value = '';
     document.getElementById('item-dia').value = '';
@@ -366,11 +390,25 @@ function clearItemForm() {
     document.getElementById('item-rate').value = '';
 }
 
+// Discount off the subtotal, mirroring the backend: flat is capped at the subtotal
+function computeDiscount(subtotal) {
+    const discountType = document.getElementById('discount-type').value;
+    const discountValue = parseFloat(document.getElementById('discount-value').value) || 0;
+    if (discountType === 'flat') {
+        return Math.min(discountValue, subtotal);
+    }
+    if (discountType === 'percent') {
+        return subtotal * (discountValue / 100);
+    }
+    return 0;
+}
+
 function updateTotals() {
     const subtotal = items.reduce((sum, item) => sum + item.amount, 0);
+    const discount = computeDiscount(subtotal);
     const gstPercent = parseFloat(document.getElementById('gst-percent').value) || 0;
-    const gstAmount = subtotal * (gstPercent / 100);
-    const total = subtotal + gstAmount;
+    const gstAmount = (subtotal - discount) * (gstPercent / 100);
+    const total = subtotal - discount + gstAmount;
 
     document.getElementById('subtotal').textContent = subtotal.toFixed(2);
     document.getElementById('gst-amount').textContent = gstAmount.toFixed(2);
@@ -380,16 +418,21 @@ function updateTotals() {
 function clearForm() {
     document.getElementById('order-no').value = '';
     document.getElementById('order-date').value = new Date().toISOString().split('T')[0];
+    document.getElementById('doc-type').value = 'order';
     document.getElementById('customer-name').value = '';
     document.getElementById('contact-person').value = '';
     document.getElementById('phone').value = '';
+    document.getElementById('gstin').value = '';
     document.getElementById('machine-name').value = '';
     document.getElementById('remarks').value = '';
     document.getElementById('delivery-note').value = '';
     document.getElementById('delivery-note-date').value = '';
     document.getElementById('buyer-order-no').value = '';
     document.getElementById('buyer-order-date').value = '';
-    document.getElementById('gst-percent').value = '18';
+    document.getElementById('due-date').value = '';
+    document.getElementById('gst-percent').value = String(defaultGstPercent);
+    document.getElementById('discount-type').value = 'none';
+    document.getElementById('discount-value').value = '0';
     items = [];
     editingItemIndex = null;
     document.getElementById('add-item-btn').textContent = 'Add Item';
@@ -400,6 +443,39 @@ function clearForm() {
     updateTotals();
 }
 
+// Defaults for new orders come from the backend settings table when available
+async function loadSettings() {
+    try {
+        const gstRate = await invoke('get_gst_rate');
+        if (typeof gstRate === 'number') {
+            defaultGstPercent = gstRate;
+            document.getElementById('gst-percent').value = String(gstRate);
+        }
+        const prefix = await invoke('get_order_prefix');
+        if (prefix) {
+            orderNoPrefix = prefix;
+        }
+    } catch (error) {
+        console.error('Failed to load settings:', error);
+    }
+}
+
+// Suggestions for the machine field come from machines already used on orders
+async function loadMachineNames() {
+    try {
+        const machines = await invoke('list_machines');
+        const list = document.getElementById('machine-names');
+        list.innerHTML = '';
+        machines.forEach(name => {
+            const option = document.createElement('option');
+            option.value = name;
+            list.appendChild(option);
+        });
+    } catch (error) {
+        console.error('Failed to load machine names:', error);
+    }
+}
+
 async function saveOrder(e) {
     e.preventDefault();
     e.stopPropagation();
@@ -433,15 +509,16 @@ async function saveOrder(e) {
     renderItems();
 
     const subtotal = items.reduce((sum, item) => sum + item.amount, 0);
+    const discount = computeDiscount(subtotal);
     const gstPercent = parseFloat(document.getElementById('gst-percent').value) || 0;
-    const gstAmount = subtotal * (gstPercent / 100);
-    const total = subtotal + gstAmount;
+    const gstAmount = (subtotal - discount) * (gstPercent / 100);
+    const total = subtotal - discount + gstAmount;
 
     let orderNo = document.getElementById('order-no').value.trim();
     
     // Auto-generate if empty
     if (!orderNo) {
-        orderNo = `ORD-${new Date().toISOString().replace(/[-:T.]/g, '').slice(0, 14)}`;
+        orderNo = `${orderNoPrefix}-${new Date().toISOString().replace(/[-:T.]/g, '').slice(0, 14)}`;
     }
     
     // Check for duplicate order number
@@ -458,14 +535,17 @@ async function saveOrder(e) {
     const order = {
         orderNo: orderNo,
         date: document.getElementById('order-date').value,
+        docType: document.getElementById('doc-type').value,
         customerName: customerName,
         contactPerson: document.getElementById('contact-person').value.trim(),
         phone: document.getElementById('phone').value.trim(),
+        gstin: document.getElementById('gstin').value.trim().toUpperCase(),
         status: 'New',
         machineName: document.getElementById('machine-name').value.trim(),
         items: items.map(item => ({
             slNo: item.slNo,
             type: item.type,
+            hsn: item.hsn || '',
             qty: item.qty,
             length: item.length,
             dia: item.dia,
@@ -475,24 +555,32 @@ async function saveOrder(e) {
             amount: item.amount
         })),
         subtotal: subtotal,
+        discountType: document.getElementById('discount-type').value,
+        discountValue: parseFloat(document.getElementById('discount-value').value) || 0,
+        discountAmount: discount,
         gst: gstAmount,
+        gstRate: gstPercent,
         total: total,
         remarks: document.getElementById('remarks').value.trim(),
         deliveryNote: document.getElementById('delivery-note').value.trim(),
         deliveryNoteDate: document.getElementById('delivery-note-date').value,
         buyerOrderNo: document.getElementById('buyer-order-no').value.trim(),
         buyerOrderDate: document.getElementById('buyer-order-date').value,
-        createdDate: new Date().toISOString()
+        dueDate: document.getElementById('due-date').value || null,
+        createdDate: new Date().toISOString(),
+        // The version we last loaded; the backend refuses the save if it has moved on
+        version: existingOrder ? (existingOrder.version || 0) : 0
     };
 
     try {
         await invoke('save_order', { order });
         alert(`Order ${orderNo} saved successfully!`);
         clearForm();
+        loadMachineNames();
         await loadOrders(true, 1);
         switchTab('view');
     } catch (error) {
-        alert(`Error saving order: ${error}`);
+        alert(`Error saving order: ${error.message || error}`);
     }
 }
 
@@ -665,7 +753,7 @@ function renderOrders(ordersList) {
             <td>${order.customerName}</td>
             <td>${order.contactPerson || ''}</td>
             <td>${order.phone || ''}</td>
-            <td><span class="status-badge status-${order.status.toLowerCase().replace(/\s+/g, '-')}">${order.status}</span></td>
+            <td><span class="status-badge status-${order.status.toLowerCase().replace(/\s+/g, '-')}">${order.status}</span>${order.printedAt ? ` <span class="status-badge status-printed" title="Printed ${new Date(order.printedAt).toLocaleString()}">Printed</span>` : ''}</td>
             <td>${order.items.length}</td>
             <td>${order.total.toFixed(2)}</td>
             <td>
@@ -690,11 +778,12 @@ function viewOrderDetails(order) {
     currentViewingOrder = order; // Store for printing
     const content = document.getElementById('order-details-content');
     
-    let itemsHtml = '<table class="details-table"><thead><tr><th>Sl.</th><th>Type</th><th>Qty</th><th>Length</th><th>Dia</th><th>Shore</th><th>Remarks</th><th>Rate</th><th>Amount</th></tr></thead><tbody>';
+    let itemsHtml = '<table class="details-table"><thead><tr><th>Sl.</th><th>Type</th><th>HSN</th><th>Qty</th><th>Length</th><th>Dia</th><th>Shore</th><th>Remarks</th><th>Rate</th><th>Amount</th></tr></thead><tbody>';
     order.items.forEach(item => {
         itemsHtml += `<tr>
             <td>${item.slNo}</td>
             <td>${item.type || ''}</td>
+            <td>${item.hsn || ''}</td>
             <td>${item.qty}</td>
             <td>${item.length || ''}</td>
             <td>${item.dia || ''}</td>
@@ -734,6 +823,10 @@ function viewOrderDetails(order) {
                         <label>Phone:</label>
                         <div class="detail-value">${order.phone || ''}</div>
                     </div>
+                    <div class="form-group">
+                        <label>GSTIN:</label>
+                        <div class="detail-value">${order.gstin || ''}</div>
+                    </div>
                 </div>
                 <div class="form-row">
                     <div class="form-group">
@@ -760,6 +853,10 @@ function viewOrderDetails(order) {
                         <label>Buyer's Order Date:</label>
                         <div class="detail-value">${order.buyerOrderDate || ''}</div>
                     </div>
+                    <div class="form-group">
+                        <label>Due Date:</label>
+                        <div class="detail-value">${order.dueDate || ''}</div>
+                    </div>
                     <div class="form-group">
                         <label>Status:</label>
                         <div class="detail-value"><span class="status-badge status-${order.status.toLowerCase().replace(/\s+/g, '-')}">${order.status}</span></div>
@@ -776,6 +873,7 @@ function viewOrderDetails(order) {
             ${itemsHtml}
             <div class="totals-summary">
                 <p><strong>Subtotal:</strong> Rs. ${order.subtotal.toFixed(2)}</p>
+                ${order.discountAmount ? `<p><strong>Discount:</strong> Rs. ${order.discountAmount.toFixed(2)}</p>` : ''}
                 <p><strong>GST:</strong> Rs. ${order.gst.toFixed(2)}</p>
                 <p><strong>Total:</strong> Rs. ${order.total.toFixed(2)}</p>
             </div>
@@ -816,7 +914,7 @@ async function saveStatus() {
         await loadOrders(true, currentPage);
         alert('Status updated successfully!');
     } catch (error) {
-        alert(`Error updating status: ${error}`);
+        alert(`Error updating status: ${error.message || error}`);
     }
 }
 
@@ -845,7 +943,7 @@ async function confirmDeleteOrder() {
         await loadOrders(true, currentPage);
         alert('Order deleted successfully!');
     } catch (error) {
-        alert(`Error deleting order: ${error}`);
+        alert(`Error deleting order: ${error.message || error}`);
     }
 }
 
@@ -864,11 +962,34 @@ async function exportExcel() {
         });
 
         if (filePath) {
-            await invoke('export_orders', { filePath });
+            const exportBtn = document.getElementById('export-btn');
+            const cancelBtn = document.getElementById('cancel-export-btn');
+            const label = exportBtn.textContent;
+            exportBtn.disabled = true;
+            cancelBtn.onclick = () => invoke('cancel_export');
+            cancelBtn.style.display = '';
+            const unlisten = listen
+                ? await listen('export-progress', (event) => {
+                    const { written, total } = event.payload;
+                    exportBtn.textContent = `Exporting... ${Math.floor(written * 100 / total)}%`;
+                })
+                : null;
+            try {
+                await invoke('export_orders', { filePath });
+            } finally {
+                if (unlisten) unlisten();
+                cancelBtn.style.display = 'none';
+                exportBtn.textContent = label;
+                exportBtn.disabled = false;
+            }
             alert('Orders exported successfully!');
         }
     } catch (error) {
-        alert(`Error exporting: ${error}`);
+        if (error.kind === 'Cancelled') {
+            alert('Export cancelled');
+            return;
+        }
+        alert(`Error exporting: ${error.message || error}`);
     }
 }
 
@@ -876,13 +997,22 @@ async function exportExcel() {
 let currentViewingOrder = null;
 
 // Generate order HTML for print/PDF
-function generateOrderHtml(order) {
+function generateOrderHtml(order, amountInWords = '', company = null) {
+    const profile = company || {};
+    const companyName = profile.name || 'AAKSO RUBBER WORKS';
+    const tagline = profile.tagline || 'Mfg: Rubber Extruded, Moulded & Lining Products';
+    const address = profile.address || 'Admn. Off/Works: D-34, Phase V, IDA, Jeedimetla, Hyderabad-500 055';
+    const email = profile.email || 'aaksorubber@gmail.com';
+    const phone = profile.phone || '9440624313, 9550884200';
+    const contactLine = [email ? `E-mail: ${email}` : '', phone ? `Ph: ${phone}` : ''].filter(Boolean).join(' | ');
+
     let itemsHtml = '';
     order.items.forEach(item => {
         itemsHtml += `
             <tr>
                 <td>${item.slNo}</td>
                 <td>${item.type || ''}</td>
+                <td>${item.hsn || ''}</td>
                 <td>${item.qty}</td>
                 <td>${item.length || ''}</td>
                 <td>${item.dia || ''}</td>
@@ -899,7 +1029,7 @@ function generateOrderHtml(order) {
         <html>
         <head>
             <meta charset="UTF-8">
-            <title>Order ${order.orderNo} - AAKSO Rubber Works</title>
+            <title>Order ${order.orderNo} - ${companyName}</title>
             <style>
                 * { margin: 0; padding: 0; box-sizing: border-box; }
                 body {
@@ -1044,10 +1174,12 @@ function generateOrderHtml(order) {
         </head>
         <body>
             <div class="print-header">
-                <h1>AAKSO RUBBER WORKS</h1>
-                <p><strong>Mfg:</strong> Rubber Extruded, Moulded & Lining Products</p>
-                <p>Admn. Off/Works: D-34, Phase V, IDA, Jeedimetla, Hyderabad-500 055</p>
-                <p>E-mail: aaksorubber@gmail.com | Ph: 9440624313, 9550884200</p>
+                ${profile.logoPath ? `<img src="${profile.logoPath}" alt="" style="max-height: 60px;">` : ''}
+                <h1>${companyName}</h1>
+                <p>${tagline}</p>
+                <p>${address}</p>
+                ${profile.gstin ? `<p>GSTIN: ${profile.gstin}</p>` : ''}
+                <p>${contactLine}</p>
             </div>
             
             <div class="print-title">WORK ORDER / QUOTATION</div>
@@ -1059,6 +1191,7 @@ function generateOrderHtml(order) {
                 <p><strong>Status:</strong> ${order.status}</p>
                 <p><strong>Contact Person:</strong> ${order.contactPerson || '-'}</p>
                 <p><strong>Phone:</strong> ${order.phone || '-'}</p>
+                <p><strong>GSTIN:</strong> ${order.gstin || '-'}</p>
                 <p><strong>Name of the Machine:</strong> ${order.machineName || '-'}</p>
                 <p><strong>Remarks:</strong> ${order.remarks || '-'}</p>
                 <p><strong>Delivery Note:</strong> ${order.deliveryNote || '-'}</p>
@@ -1072,6 +1205,7 @@ function generateOrderHtml(order) {
                     <tr>
                         <th>Sl.</th>
                         <th>Type</th>
+                        <th>HSN</th>
                         <th>Qty</th>
                         <th>Length</th>
                         <th>Dia</th>
@@ -1088,8 +1222,10 @@ function generateOrderHtml(order) {
 
             <div class="print-totals">
                 <p><strong>Subtotal:</strong> Rs. ${order.subtotal.toFixed(2)}</p>
+                ${order.discountAmount ? `<p><strong>Discount${order.discountType === 'percent' ? ` (${order.discountValue}%)` : ''}:</strong> Rs. ${order.discountAmount.toFixed(2)}</p>` : ''}
                 <p><strong>GST:</strong> Rs. ${order.gst.toFixed(2)}</p>
                 <p class="total-final"><strong>Total:</strong> Rs. ${order.total.toFixed(2)}</p>
+                ${amountInWords ? `<p><strong>Amount in words:</strong> ${amountInWords}</p>` : ''}
             </div>
 
             <div class="print-footer">
@@ -1111,6 +1247,17 @@ function generateOrderHtml(order) {
     `;
 }
 
+// Stamps the order as printed so the list can show it; the stamp is cleared on edit
+async function markOrderPrinted(order) {
+    try {
+        await invoke('mark_printed', { orderNo: order.orderNo });
+        order.printedAt = new Date().toISOString();
+        applyFiltersAndSort();
+    } catch (error) {
+        console.error('Failed to mark order as printed:', error);
+    }
+}
+
 async function saveOrderAsPdf() {
     if (!currentViewingOrder) {
         alert('No order selected');
@@ -1118,7 +1265,18 @@ async function saveOrderAsPdf() {
     }
 
     const order = currentViewingOrder;
-    const htmlContent = generateOrderHtml(order);
+    let amountInWords = '';
+    let company = null;
+    try {
+        const invoiceData = await invoke('get_invoice_data', { orderNo: order.orderNo });
+        if (invoiceData) {
+            amountInWords = invoiceData.amountInWords || '';
+            company = invoiceData.company;
+        }
+    } catch (error) {
+        console.error('Failed to load invoice data:', error);
+    }
+    const htmlContent = generateOrderHtml(order, amountInWords, company);
     
     try {
         // Check if html2pdf is available
@@ -1153,6 +1311,7 @@ async function saveOrderAsPdf() {
                 
                 await html2pdf().set(opt).from(tempDiv).save();
                 document.body.removeChild(tempDiv);
+                await markOrderPrinted(order);
                 alert(`Order saved as PDF: Order_${order.orderNo}.pdf`);
                 return;
             } catch (pdfError) {
@@ -1177,6 +1336,7 @@ async function saveOrderAsPdf() {
                     filePath: filePath, 
                     content: htmlContent 
                 });
+                await markOrderPrinted(order);
                 alert(`Order saved as HTML!\n\nTo convert to PDF:\n1. Open the saved file in your browser\n2. Press Ctrl+P (or Cmd+P on Mac)\n3. Choose "Save as PDF" as the destination`);
                 return;
             }